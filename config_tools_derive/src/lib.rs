@@ -3,20 +3,20 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(FromSection)]
+#[proc_macro_derive(FromSection, attributes(section))]
 #[doc = r#"
     Derives the `Section` trait for a struct.
 
-    This macro generates an implementation of the `Section` trait for a struct, 
-    enabling automatic parsing of its fields from a `BTreeMap<String, String>`, 
+    This macro generates an implementation of the `Section` trait for a struct,
+    enabling automatic parsing of its fields from a `BTreeMap<String, String>`,
     which represents a section from a configuration file.
 
-    Each field in the struct must implement `FromStr`, as the macro will attempt 
+    Each field in the struct must implement `FromStr`, as the macro will attempt
     to parse the corresponding string value for each field in the section map.
 
     # Example
 
-    ```rust
+    ```rust,ignore
     #[derive(FromSection)]
     struct ServerConfig {
         host: String,
@@ -25,16 +25,42 @@ use syn::{parse_macro_input, DeriveInput};
 
     let config = Config::load("config.ini")?;
     let server_section = config.section("Server").unwrap();
-    let server_config = ServerConfig::from_section(server_section)?;
+    let server_config = ServerConfig::from_section(&server_section)?;
     println!("{:?}", server_config);
     ```
 
     In this example, the `ServerConfig` struct will automatically be populated
-    from the `[Server]` section of the `config.ini` file, with values for 
+    from the `[Server]` section of the `config.ini` file, with values for
     `host` and `port`.
+
+    # Command-line arguments
+
+    With the `clap` feature enabled, the macro also implements `ClapSection`,
+    giving each field a `--field-name <VALUE>` argument. Doc comments on fields
+    become the argument help text, and environment variables can be attached
+    with the `section` attribute:
+
+    ```rust,ignore
+    #[derive(FromSection)]
+    #[section(env_prefix = "MYAPP_")]
+    struct ServerConfig {
+        /// Address to bind to.
+        host: String,
+        #[section(env = "PORT")]
+        port: u16,
+    }
+    ```
+
+    Here `host` reads `MYAPP_HOST` and `port` reads `PORT`.
 "#]
 pub fn from_section_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &input.ident;
     let fields = match &input.data {
         syn::Data::Struct(data_struct) => match &data_struct.fields {
@@ -44,18 +70,36 @@ pub fn from_section_derive(input: TokenStream) -> TokenStream {
         _ => panic!("Only structs are supported"),
     };
 
+    // The `section` attribute only affects the `clap` implementation, but
+    // mistakes in it are reported either way.
+    section_attr(&input.attrs, "env_prefix")?;
+    for field in fields {
+        section_attr(&field.attrs, "env")?;
+    }
+
     let field_parsing = fields.iter().map(|f| {
         let field_name = f.ident.as_ref().unwrap();
         let field_type = &f.ty;
 
         quote! {
-            #field_name: map.get(stringify!(#field_name))
-                .and_then(|value| value.parse::<#field_type>().ok())
-                .ok_or_else(|| config_tools::Error::ConfigParse(format!("Failed to parse field '{}'", stringify!(#field_name))))?,
+            #field_name: {
+                let error = |message: String| config_tools::Error::ConfigParse {
+                    section: None,
+                    key: Some(stringify!(#field_name).to_string()),
+                    message,
+                };
+                let value = map
+                    .get(stringify!(#field_name))
+                    .ok_or_else(|| error("Missing value".to_string()))?;
+                value
+                    .parse::<#field_type>()
+                    .map_err(|_| error(format!("Invalid value '{}'", value)))?
+            },
         }
     });
 
-    let expanded = quote! {
+    #[allow(unused_mut)]
+    let mut expanded = quote! {
         impl config_tools::Section for #name {
             fn from_section(map: &std::collections::BTreeMap<String, String>) -> Result<Self, config_tools::Error> {
                 Ok(Self {
@@ -65,5 +109,99 @@ pub fn from_section_derive(input: TokenStream) -> TokenStream {
         }
     };
 
-    TokenStream::from(expanded)
-}
\ No newline at end of file
+    #[cfg(feature = "clap")]
+    expanded.extend(clap_section(name, &input.attrs, fields)?);
+
+    Ok(expanded)
+}
+
+/// Generates the `ClapSection` implementation, declaring one argument per field.
+#[cfg(feature = "clap")]
+fn clap_section(
+    name: &syn::Ident,
+    attrs: &[syn::Attribute],
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let env_prefix = section_attr(attrs, "env_prefix")?;
+
+    let mut args = Vec::new();
+    for f in fields {
+        let field_name = f.ident.as_ref().unwrap().to_string();
+        let long = field_name.replace('_', "-");
+        let value_name = field_name.to_uppercase();
+
+        let env = section_attr(&f.attrs, "env")?
+            .or_else(|| {
+                env_prefix
+                    .as_ref()
+                    .map(|prefix| format!("{prefix}{value_name}"))
+            })
+            .map(|env| quote! { .env(#env) });
+
+        let help = doc_comment(&f.attrs).map(|help| quote! { .help(#help) });
+
+        args.push(quote! {
+            config_tools::clap::Arg::new(#field_name)
+                .long(#long)
+                .value_name(#value_name)
+                .action(config_tools::clap::ArgAction::Set)
+                #env
+                #help
+        });
+    }
+
+    Ok(quote! {
+        impl config_tools::ClapSection for #name {
+            fn args() -> Vec<config_tools::clap::Arg> {
+                vec![#(#args),*]
+            }
+        }
+    })
+}
+
+/// Reads `#[section(name = "value")]` from a list of attributes. Any other
+/// key is an error, since `name` is the only one allowed in that position.
+fn section_attr(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<String>> {
+    let mut found = None;
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("section")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident(name) {
+                return Err(meta.error(format!(
+                    "unsupported section attribute, expected `{name} = \"...\"`"
+                )));
+            }
+            let value: syn::LitStr = meta.value()?.parse()?;
+            found = Some(value.value());
+            Ok(())
+        })?;
+    }
+
+    Ok(found)
+}
+
+/// Joins the `///` doc comment lines of a field into a single help string.
+#[cfg(feature = "clap")]
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }),
+                ..
+            }) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+use config_tools::{clap::Command, sectioned_defaults, ClapSection, Config, FromSection};
+use tempfile::NamedTempFile;
+
+#[derive(Debug, FromSection)]
+#[section(env_prefix = "EXAMPLE_")]
+struct ServerSettings {
+    /// Address to bind to.
+    address: String,
+    /// Port to listen on.
+    port: u16,
+    /// Number of worker threads.
+    threads: u16,
+}
+
+fn main() {
+    // Try `cargo run --example clap_section --features clap -- --port 9000`
+    // or `EXAMPLE_THREADS=16 cargo run --example clap_section --features clap`.
+    let matches = ServerSettings::augment_command(Command::new("server")).get_matches();
+
+    let defaults = sectioned_defaults! {
+        ["Server"] {
+            "address" => "127.0.0.1",
+            "port" => "8080",
+            "threads" => "4",
+        }
+    };
+
+    let config = Config::load_or_default(NamedTempFile::new().unwrap().path(), defaults.clone());
+
+    let server_settings = ServerSettings::from_sources(
+        &matches,
+        config.section("Server").as_deref(),
+        defaults.section("Server").as_deref(),
+    )
+    .unwrap();
+
+    println!("{server_settings:#?}");
+}
@@ -0,0 +1,128 @@
+use crate::{permissions, WriteOptions};
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Replaces the contents of `path` without ever leaving a partly written
+/// file behind.
+///
+/// The contents are written to a temporary file in the same directory,
+/// flushed to disk and renamed over the target, so readers see either the
+/// old file or the new one. The target's permissions are kept unless
+/// [`WriteOptions::mode`] says otherwise. With [`WriteOptions::backup`], the
+/// previous version is first copied to `<path>.bak`.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8], options: &WriteOptions) -> io::Result<()> {
+    // Replace the file a symlink points to rather than the link itself.
+    let path = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let existing = fs::metadata(&path).ok();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (tmp_path, mut tmp) = create_temp(&dir, &path, options.mode)?;
+    let written = (|| {
+        permissions::apply_write_options(&tmp, existing.as_ref(), options)?;
+        tmp.write_all(contents)?;
+        tmp.sync_all()?;
+
+        if options.backup && existing.is_some() {
+            fs::copy(&path, backup_path(&path))?;
+        }
+        fs::rename(&tmp_path, &path)
+    })();
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    sync_dir(&dir);
+    Ok(())
+}
+
+/// Creates `path` with the given contents, failing with
+/// [`io::ErrorKind::AlreadyExists`] if it exists.
+///
+/// Like [`write_atomic`], the contents are written to a temporary file
+/// first, which is then hard-linked to `path`, so the file never appears
+/// partly written and an existing file is never replaced.
+pub(crate) fn create_atomic(
+    path: &Path,
+    contents: &[u8],
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (tmp_path, mut tmp) = create_temp(&dir, path, options.mode)?;
+    let written = (|| {
+        permissions::apply_write_options(&tmp, None, options)?;
+        tmp.write_all(contents)?;
+        tmp.sync_all()?;
+        fs::hard_link(&tmp_path, path)
+    })();
+
+    let _ = fs::remove_file(&tmp_path);
+    written?;
+    sync_dir(&dir);
+    Ok(())
+}
+
+/// Where [`write_atomic`] keeps the previous version of a file.
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Creates the temporary file. With a `mode`, it is never more permissive
+/// than that, even before its permissions are set.
+fn create_temp(dir: &Path, path: &Path, mode: Option<u32>) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().unwrap_or_default();
+
+    loop {
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(name);
+        tmp_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let tmp_path = dir.join(tmp_name);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        match options.open(&tmp_path) {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Flushes the rename itself to disk. Not every platform can open a
+/// directory, so failures are ignored.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
@@ -7,6 +7,7 @@ use crate::Config;
 pub struct ConfigBuilder {
     pub(crate) config: Config,
     pub(crate) section: Option<String>,
+    pub(crate) comment: Option<String>,
 }
 
 impl ConfigBuilder {
@@ -14,6 +15,13 @@ impl ConfigBuilder {
         self.config
     }
 
+    /// Attaches a comment to whatever comes next: the section started by
+    /// [`ConfigBuilder::section`] or the key set by [`ConfigBuilder::set`].
+    pub fn comment(mut self, text: &str) -> Self {
+        self.comment = Some(text.to_string());
+        self
+    }
+
     pub fn general(mut self) -> Self {
         self.section = None;
         self
@@ -22,22 +30,32 @@ impl ConfigBuilder {
     pub fn section(mut self, title: &str) -> Self {
         self.section = Some(title.to_string());
         self.config.sections.entry(title.to_string()).or_default();
+        self.config.order.add_section(title);
+        if let Some(comment) = self.comment.take() {
+            self.config.set_section_comment(title, &comment);
+        }
         self
     }
 
     pub fn set(mut self, key: &str, value: &str) -> Self {
-        if let Some(section) = self.section.as_ref() {
+        if let Some(comment) = self.comment.take() {
             self.config
-                .sections
-                .entry(section.clone())
-                .or_default()
-                .insert(key.to_string(), value.to_string());
-        } else {
+                .set_comment(self.section.as_deref(), key, &comment);
+        }
+
+        self.config.update(self.section.as_deref(), key, value);
+        self
+    }
+
+    /// Adds another value to a key instead of replacing it, as in
+    /// `server = a` / `server = b`. See [`Config::get_all`].
+    pub fn add(mut self, key: &str, value: &str) -> Self {
+        if let Some(comment) = self.comment.take() {
             self.config
-                .general_values
-                .insert(key.to_string(), value.to_string());
+                .set_comment(self.section.as_deref(), key, &comment);
         }
 
+        self.config.add(self.section.as_deref(), key, value);
         self
     }
 }
@@ -0,0 +1,50 @@
+use crate::{Error, Section};
+use clap::{parser::ValueSource, ArgMatches, Command};
+use std::collections::BTreeMap;
+
+/// A [`Section`] that can also be filled from command-line arguments and
+/// environment variables.
+///
+/// Implemented automatically by `#[derive(FromSection)]` when the `clap`
+/// feature is enabled. Values are layered with the precedence
+/// CLI > env > file > defaults, and the merged map is then handed to
+/// [`Section::from_section`].
+pub trait ClapSection: Section {
+    /// Returns one `--field-name <VALUE>` argument for each field.
+    fn args() -> Vec<clap::Arg>;
+
+    /// Adds the arguments from [`ClapSection::args`] to `cmd`.
+    fn augment_command(cmd: Command) -> Command {
+        cmd.args(Self::args())
+    }
+
+    /// Builds `Self` from parsed arguments, a section loaded from a file and
+    /// a section of default values.
+    ///
+    /// Arguments only override the file and defaults when they were given on
+    /// the command line or through their environment variable.
+    fn from_sources(
+        matches: &ArgMatches,
+        file: Option<&BTreeMap<String, String>>,
+        defaults: Option<&BTreeMap<String, String>>,
+    ) -> Result<Self, Error> {
+        let mut map = defaults.cloned().unwrap_or_default();
+        if let Some(file) = file {
+            map.extend(file.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
+        for arg in Self::args() {
+            let id = arg.get_id().as_str();
+            let explicit = matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            );
+
+            if let (true, Ok(Some(value))) = (explicit, matches.try_get_one::<String>(id)) {
+                map.insert(id.to_string(), value.clone());
+            }
+        }
+
+        Self::from_section(&map)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::Config;
+use std::collections::BTreeMap;
+
+/// Comments attached to the keys and section headers of a [`Config`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub(crate) struct Comments {
+    #[serde(default)]
+    pub(crate) general: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) sections: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) keys: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Comments {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.general.is_empty() && self.sections.is_empty() && self.keys.is_empty()
+    }
+
+    pub(crate) fn get(&self, section: Option<&str>, key: &str) -> Option<&str> {
+        match section {
+            Some(section) => self.keys.get(section).and_then(|s| s.get(key)),
+            None => self.general.get(key),
+        }
+        .map(String::as_str)
+    }
+
+    pub(crate) fn set(&mut self, section: Option<&str>, key: &str, text: &str) {
+        let comments = match section {
+            Some(section) => self.keys.entry(section.to_string()).or_default(),
+            None => &mut self.general,
+        };
+
+        comments.insert(key.to_string(), text.to_string());
+    }
+
+    pub(crate) fn remove(&mut self, section: Option<&str>, key: &str) {
+        match section {
+            Some(section) => {
+                if let Some(keys) = self.keys.get_mut(section) {
+                    keys.remove(key);
+                }
+            }
+            None => {
+                self.general.remove(key);
+            }
+        }
+    }
+
+    pub(crate) fn remove_section(&mut self, title: &str) {
+        self.sections.remove(title);
+        self.keys.remove(title);
+    }
+
+    pub(crate) fn merge(&mut self, other: Comments) {
+        self.general.extend(other.general);
+        self.sections.extend(other.sections);
+
+        for (title, keys) in other.keys {
+            self.keys.entry(title).or_default().extend(keys);
+        }
+    }
+}
+
+impl Config {
+    /// Returns the comment written above a key, without its `;` or `#` markers.
+    pub fn comment(&self, section: Option<&str>, key: &str) -> Option<&str> {
+        self.comments.get(section, key)
+    }
+
+    /// Attaches a comment to a key. It is written on the lines above the key
+    /// by [`Config::save`]; use `\n` for a comment spanning several lines.
+    pub fn set_comment(&mut self, section: Option<&str>, key: &str, text: &str) -> &mut Self {
+        self.comments.set(section, key, text);
+        self
+    }
+
+    /// Returns the comment written above a section header.
+    pub fn section_comment(&self, title: &str) -> Option<&str> {
+        self.comments.sections.get(title).map(String::as_str)
+    }
+
+    /// Attaches a comment to a section header.
+    pub fn set_section_comment(&mut self, title: &str, text: &str) -> &mut Self {
+        self.comments
+            .sections
+            .insert(title.to_string(), text.to_string());
+        self
+    }
+}
+
+/// Returns the text of a comment line, or `None` if it isn't a comment.
+pub(crate) fn comment_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix([';', '#'])?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest).trim_end())
+}
+
+/// Renders comment text as one comment line per line of text.
+pub(crate) fn comment_lines(text: &str, marker: char) -> impl Iterator<Item = String> + '_ {
+    text.lines().map(move |line| match line {
+        "" => marker.to_string(),
+        line => format!("{marker} {line}"),
+    })
+}
@@ -1,6 +1,28 @@
-use crate::{builder::ConfigBuilder, error::Error, outcome::LoadOutcome};
+use crate::{
+    atomic,
+    builder::ConfigBuilder,
+    comment::Comments,
+    document::ConfigDocument,
+    error::{Error, ParseError},
+    fingerprint::Origin,
+    include,
+    lock::FileLock,
+    multi::MultiValues,
+    options::{ParseOptions, WriteOptions},
+    order::Order,
+    outcome::LoadOutcome,
+    permissions,
+};
 use ini::Ini;
-use std::{collections::BTreeMap, path::Path};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 pub trait Section: Sized {
     fn from_section(map: &BTreeMap<String, String>) -> Result<Self, Error>;
@@ -12,22 +34,67 @@ pub trait Section: Sized {
 /// You can build a `Config` manually using the [`ConfigBuilder`] API,
 /// load one from a file, or create defaults using macros like
 /// [`crate::sectioned_defaults!`] and [`crate::general_defaults!`].
-#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+///
+/// Two configs are equal when they hold the same values, including every
+/// value of repeated keys; comments and the file they were loaded from are
+/// not compared.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Config {
     pub sections: BTreeMap<String, BTreeMap<String, String>>,
     pub general_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Comments::is_empty")]
+    pub(crate) comments: Comments,
+    #[serde(default, skip_serializing_if = "MultiValues::is_empty")]
+    pub(crate) multi: MultiValues,
+    /// The section each section inherits from, read with
+    /// [`ParseOptions::inheritance`]. Sections only hold their own keys;
+    /// [`Config::section`] adds the inherited ones.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) parents: BTreeMap<String, String>,
+    /// Whether `configparser` semantics apply: keys in `[DEFAULT]` are
+    /// fallbacks for every other section, and `%(name)s` references are
+    /// expanded on lookup. Set by [`Config::apply_configparser`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) configparser: bool,
+    /// The order sections and keys were read or added in.
+    #[serde(skip)]
+    pub(crate) order: Order,
+    /// The files merged in by `include` directives. Saving is refused while
+    /// this is not empty.
+    #[serde(skip)]
+    pub(crate) includes: Vec<PathBuf>,
+    /// The file this config was loaded from, for [`Config::save_checked`].
+    #[serde(skip)]
+    pub(crate) origin: Option<Arc<Origin>>,
 }
 
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        self.sections == other.sections
+            && self.general_values == other.general_values
+            && self.repeated() == other.repeated()
+            && self.parents == other.parents
+            && self.configparser == other.configparser
+    }
+}
+
+impl Eq for Config {}
+
 impl Config {
     pub fn general(&self) -> &BTreeMap<String, String> {
         &self.general_values
     }
 
+    /// Returns the value of a key. With inheritance, keys missing from a
+    /// section are looked up in its parents. After
+    /// [`Config::apply_configparser`], `%(name)s` references are expanded.
     pub fn get(&self, section: Option<&str>, key: &str) -> Option<String> {
-        if let Some(section) = section {
-            return self.sections.get(section).and_then(|s| s.get(key)).cloned();
+        if let Some(title) = section {
+            let section = self.defining_section(title, key);
+            let value = self.sections.get(section).and_then(|s| s.get(key))?;
+            Some(self.interpolate(title, key, value))
         } else {
-            return self.general_values.get(key).cloned();
+            self.general_values.get(key).cloned()
         }
     }
 
@@ -39,29 +106,110 @@ impl Config {
     }
 
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let ini = Ini::load_from_file(path).map_err(Error::ConfigLoad)?;
+        Self::load_with(path, &ParseOptions::default())
+    }
+
+    /// Loads a file using a specific INI dialect.
+    pub fn load_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self, Error> {
+        let config = Self::read_all(path.as_ref(), options)?;
+        config.check_parents()?;
+        Ok(config)
+    }
+
+    /// Like [`Config::load_with`], but without checking parent sections.
+    pub(crate) fn read_all(path: &Path, options: &ParseOptions) -> Result<Self, Error> {
+        if options.includes {
+            include::load_recursive(path, options, &mut Vec::new())
+        } else {
+            Self::read(path, options)
+        }
+    }
+
+    /// Parses a config from any reader, such as stdin or a network stream.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|source| Error::ConfigLoad { path: None, source })?;
+
+        text.parse()
+    }
+
+    /// Loads a single file without checking its parent sections or following
+    /// includes, so that it can be merged with other files first. The lock
+    /// and permission checks in `options` apply.
+    pub(crate) fn read<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let _lock = options.lock.then(|| FileLock::shared(path)).transpose()?;
+
+        let origin = Origin::read(path, options).map_err(|e| Error::load(path, e))?;
+        let (_, mut config) =
+            Self::parse_ini(origin.text(), options).map_err(|e| e.with_path(path))?;
+        permissions::check_secrets(path, &config, options)?;
+        config.origin = Some(origin);
+        Ok(config)
+    }
+
+    /// Parses INI text, keeping the comments above keys and sections.
+    pub(crate) fn parse_ini(text: &str, options: &ParseOptions) -> Result<(Ini, Self), Error> {
+        let normalized = options.normalize(text);
+        let doc = ConfigDocument::parse_with(&normalized, options);
+        if options.strict {
+            doc.check_strict(text, options)?;
+        }
+
+        let mut ini = Ini::load_from_str_opt(&normalized, options.to_ini()).map_err(|e| {
+            // The underlying parser often only notices a broken line at
+            // the end of the file, so point at the line itself instead.
+            let error = doc
+                .malformed_line(text)
+                .unwrap_or_else(|| ParseError::new(text, e.line, e.col, e.msg));
+            Error::Parse(error)
+        })?;
+        options.unescape_keys(&mut ini);
+
+        let mut config = Self::from_ini(&ini);
+        config.comments = doc.comments();
+        if options.inheritance {
+            config.link_parents();
+        }
+        Ok((ini, config))
+    }
+
+    pub(crate) fn from_ini(ini: &Ini) -> Self {
         let mut sections = BTreeMap::new();
         let mut general_values = BTreeMap::new();
+        let mut multi = MultiValues::default();
+        let mut order = Order::default();
 
         for (section, prop) in ini.iter() {
             if let Some(section) = section {
                 let mut section_map = BTreeMap::new();
+                order.add_section(section);
                 prop.iter().for_each(|(key, value)| {
                     section_map.insert(key.to_string(), value.to_string());
+                    order.add(Some(section), key);
                 });
 
+                collect_repeated(prop, Some(section), &mut multi);
                 sections.insert(section.to_string(), section_map);
             } else {
                 prop.iter().for_each(|(key, value)| {
                     general_values.insert(key.to_string(), value.to_string());
-                })
+                    order.add(None, key);
+                });
+
+                collect_repeated(prop, None, &mut multi);
             }
         }
 
-        Ok(Config {
+        Config {
             sections,
             general_values,
-        })
+            multi,
+            order,
+            ..Config::default()
+        }
     }
 
     pub fn load_or_default<P: AsRef<Path>>(path: P, default: Config) -> Self {
@@ -74,7 +222,52 @@ impl Config {
     pub fn load_or_default_outcome<P: AsRef<Path>>(path: P, default: Config) -> LoadOutcome {
         match Self::load(path) {
             Ok(config) => LoadOutcome::FromFile(config),
-            Err(_) => LoadOutcome::FromDefault(default),
+            Err(e) => LoadOutcome::FromDefault(default, e.into()),
+        }
+    }
+
+    /// Loads a file, or writes `defaults` to it if it doesn't exist yet.
+    ///
+    /// Missing parent directories are created. A file that exists but can't
+    /// be read or parsed is never overwritten; the error is returned instead.
+    pub fn load_or_create<P: AsRef<Path>>(path: P, defaults: Config) -> Result<LoadOutcome, Error> {
+        Self::load_or_create_with(path, defaults, &WriteOptions::default())
+    }
+
+    /// [`Config::load_or_create`] writing the defaults with the given
+    /// options, such as [`WriteOptions::mode`] for files holding secrets.
+    ///
+    /// The file only appears once it is completely written, and a file
+    /// created in the meantime by another process is loaded instead of
+    /// being replaced.
+    pub fn load_or_create_with<P: AsRef<Path>>(
+        path: P,
+        defaults: Config,
+        options: &WriteOptions,
+    ) -> Result<LoadOutcome, Error> {
+        let path = path.as_ref();
+        match Self::load(path) {
+            Ok(config) => return Ok(LoadOutcome::FromFile(config)),
+            Err(Error::ConfigLoad { source, .. }) if source.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| Error::create(parent, e))?;
+        }
+
+        let _lock = options
+            .lock
+            .then(|| FileLock::exclusive(path))
+            .transpose()?;
+        let text = ConfigDocument::from_config_with(&defaults, options).to_string();
+
+        match atomic::create_atomic(path, text.as_bytes(), options) {
+            Ok(()) => Ok(LoadOutcome::Created(defaults)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                Self::load(path).map(LoadOutcome::FromFile)
+            }
+            Err(e) => Err(Error::create(path, e)),
         }
     }
 
@@ -82,38 +275,121 @@ impl Config {
         ConfigBuilder {
             config: Config::default(),
             section: None,
+            comment: None,
         }
     }
 
+    /// Saves to a file. The config is written to a temporary file in the
+    /// same directory, flushed to disk and renamed over `path`, so a crash
+    /// or full disk never leaves a truncated file behind.
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<&Self, Error> {
-        let mut ini = Ini::new();
+        self.save_with(path, &WriteOptions::default())
+    }
 
-        let mut section = ini.with_general_section();
-        for (key, value) in &self.general_values {
-            section.set(key, value);
-        }
+    /// Saves to a file using a specific INI dialect.
+    ///
+    /// Like [`Config::save`], the file is replaced atomically and keeps its
+    /// permissions; set [`WriteOptions::backup`] to keep the previous version.
+    /// Configs that merged in included files can't be saved; see
+    /// [`Config::included_files`].
+    pub fn save_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &WriteOptions,
+    ) -> Result<&Self, Error> {
+        let path = path.as_ref();
+        self.check_includes(path)?;
+        let _lock = options
+            .lock
+            .then(|| FileLock::exclusive(path))
+            .transpose()?;
 
-        for (title, prop) in &self.sections {
-            let mut section = ini.with_section(Some(title));
-            for (key, value) in prop {
-                section.set(key, value);
+        ConfigDocument::from_config_with(self, options).save_to(path, options)?;
+        Ok(self)
+    }
+
+    /// Merges `other` into this config. Values from `other` replace existing
+    /// values with the same section and key; everything else is kept.
+    pub fn merge(&mut self, other: Config) -> &mut Self {
+        for key in other.general_values.keys() {
+            self.multi.remove(None, key);
+        }
+        for (title, prop) in &other.sections {
+            for key in prop.keys() {
+                self.multi.remove(Some(title), key);
             }
         }
 
-        ini.write_to_file(path).map_err(Error::ConfigCreation)?;
+        self.general_values.extend(other.general_values);
+        self.comments.merge(other.comments);
+        self.multi.merge(other.multi);
+        self.order.merge(&other.order);
+        self.parents.extend(other.parents);
+        self.configparser |= other.configparser;
+        self.includes.extend(other.includes);
+
+        for (title, prop) in other.sections {
+            self.sections.entry(title).or_default().extend(prop);
+        }
+
+        self
+    }
+
+    /// Removes a key and its comment, returning its value if it was present.
+    pub fn remove(&mut self, section: Option<&str>, key: &str) -> Option<String> {
+        self.comments.remove(section, key);
+        self.multi.remove(section, key);
+        self.order.remove(section, key);
+        match section {
+            Some(section) => self.sections.get_mut(section).and_then(|s| s.remove(key)),
+            None => self.general_values.remove(key),
+        }
+    }
+
+    /// Removes a section and all of its keys, along with their comments.
+    pub fn remove_section(&mut self, title: &str) -> Option<BTreeMap<String, String>> {
+        self.comments.remove_section(title);
+        self.multi.sections.remove(title);
+        self.parents.remove(title);
+        self.order.remove_section(title);
+        self.sections.remove(title)
+    }
 
+    /// Writes the config in INI format, as [`Config::save`] would.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<&Self, Error> {
+        write!(writer, "{self}").map_err(|source| Error::ConfigCreation { path: None, source })?;
         Ok(self)
     }
 
-    pub fn section(&self, title: &str) -> Option<&BTreeMap<String, String>> {
-        self.sections.get(title)
+    /// Returns every key a section sees: its own, then those it inherits
+    /// and the `[DEFAULT]` fallbacks of [`Config::apply_configparser`],
+    /// whose `%(name)s` references are expanded. Otherwise the section is
+    /// borrowed as it is; its own raw keys are always in
+    /// [`Config::sections`].
+    ///
+    /// Use `as_deref()` where an `Option<&BTreeMap>` is needed.
+    pub fn section(&self, title: &str) -> Option<Cow<'_, BTreeMap<String, String>>> {
+        let section = self.resolved_section(title)?;
+        if !self.configparser {
+            return Some(section);
+        }
+
+        Some(Cow::Owned(self.interpolate_section(title, &section)))
     }
 
     pub fn sections(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
         &self.sections
     }
 
+    /// Sets a value, replacing every value the key had before.
     pub fn update(&mut self, section: Option<&str>, key: &str, value: &str) -> &mut Self {
+        self.multi.remove(section, key);
+        self.insert(section, key, value);
+        self
+    }
+
+    pub(crate) fn insert(&mut self, section: Option<&str>, key: &str, value: &str) {
+        self.order.add(section, key);
         if let Some(section) = section {
             self.sections
                 .entry(section.to_string())
@@ -123,7 +399,35 @@ impl Config {
             self.general_values
                 .insert(key.to_string(), value.to_string());
         }
+    }
+}
 
-        self
+/// Records every value of the keys that appear more than once in `prop`.
+fn collect_repeated(prop: &ini::Properties, section: Option<&str>, multi: &mut MultiValues) {
+    for (key, _) in prop.iter() {
+        if multi.get(section, key).is_some() {
+            continue;
+        }
+
+        let values: Vec<String> = prop.get_all(key).map(str::to_string).collect();
+        if values.len() > 1 {
+            *multi.entry(section, key) = values;
+        }
+    }
+}
+
+/// Formats the config as INI text, in the same layout as [`Config::save`].
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ConfigDocument::from_config(self).fmt(f)
+    }
+}
+
+/// Parses INI text as [`Config::load`] does.
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse_ini(s, &ParseOptions::default())?.1)
     }
 }
@@ -0,0 +1,169 @@
+use crate::{Config, Error};
+use std::{collections::BTreeMap, path::Path};
+
+/// The section whose keys act as fallbacks for every other section.
+pub const DEFAULT_SECTION: &str = "DEFAULT";
+
+impl Config {
+    /// Loads a file with the semantics of Python's `configparser`.
+    ///
+    /// This is [`Config::load`] followed by [`Config::apply_configparser`].
+    pub fn load_configparser<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut config = Self::load(path)?;
+        config.apply_configparser()?;
+        Ok(config)
+    }
+
+    /// Applies `configparser` semantics to this config.
+    ///
+    /// Keys in the `[DEFAULT]` section become fallbacks for every other
+    /// section: lookups such as [`Config::get`] and [`Config::section`] find
+    /// them in any section that doesn't set them, but they are only stored,
+    /// and saved, in `[DEFAULT]`. Like in Python, values are stored as they
+    /// were written and `%(name)s` references are expanded on lookup, using
+    /// the keys the section sees, so a default referring to a key that a
+    /// section overrides expands differently in that section. `%%` is a
+    /// literal `%`. Values outside any section are left as they are.
+    ///
+    /// Every value is checked once here. Values set later use the same
+    /// syntax; one that can't be expanded is returned as it is.
+    ///
+    /// Unlike `configparser`, option names are not lowercased, so
+    /// references must match the case of the key they refer to.
+    ///
+    /// On error the config is left unchanged.
+    pub fn apply_configparser(&mut self) -> Result<&mut Self, Error> {
+        let mut config = self.clone();
+        config.configparser = true;
+
+        for title in config.sections.keys() {
+            let Some(section) = config.resolved_section(title) else {
+                continue;
+            };
+            let scope = Scope {
+                title,
+                keys: &section,
+            };
+
+            for key in section.keys() {
+                for value in config.raw_values(Some(title), key) {
+                    scope.expand_value(key, value, &mut vec![key.clone()])?;
+                }
+            }
+        }
+
+        *self = config;
+        Ok(self)
+    }
+
+    /// Expands the `%(name)s` references in `raw`, a value of `key` as seen
+    /// from section `title`, if `configparser` semantics apply.
+    pub(crate) fn interpolate(&self, title: &str, key: &str, raw: &str) -> String {
+        if !self.configparser {
+            return raw.to_string();
+        }
+
+        match self.resolved_section(title) {
+            Some(section) => Scope {
+                title,
+                keys: &section,
+            }
+            .expand_or_keep(key, raw),
+            None => raw.to_string(),
+        }
+    }
+
+    /// Expands every value of `section`, the keys seen from section `title`.
+    pub(crate) fn interpolate_section(
+        &self,
+        title: &str,
+        section: &BTreeMap<String, String>,
+    ) -> BTreeMap<String, String> {
+        let scope = Scope {
+            title,
+            keys: section,
+        };
+
+        section
+            .iter()
+            .map(|(key, raw)| (key.clone(), scope.expand_or_keep(key, raw)))
+            .collect()
+    }
+}
+
+/// The keys `%(name)s` references in one section can use: its own keys,
+/// those it inherits and those in `[DEFAULT]`.
+struct Scope<'a> {
+    title: &'a str,
+    keys: &'a BTreeMap<String, String>,
+}
+
+impl Scope<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.keys.get(name).map(String::as_str)
+    }
+
+    /// Expands `raw`, or returns it unchanged if it can't be expanded.
+    fn expand_or_keep(&self, key: &str, raw: &str) -> String {
+        self.expand_value(key, raw, &mut vec![key.to_string()])
+            .unwrap_or_else(|_| raw.to_string())
+    }
+
+    fn expand(&self, key: &str, stack: &mut Vec<String>) -> Result<String, Error> {
+        if let Some(start) = stack.iter().position(|k| k == key) {
+            let mut chain: Vec<String> = stack[start..]
+                .iter()
+                .map(|k| format!("{}:{k}", self.title))
+                .collect();
+            chain.push(format!("{}:{key}", self.title));
+            return Err(Error::InterpolationCycle(chain));
+        }
+
+        let raw = self.get(key).unwrap_or_default();
+        stack.push(key.to_string());
+        let expanded = self.expand_value(key, raw, stack);
+        stack.pop();
+        expanded
+    }
+
+    /// Expands the references in `raw`, one of the values of `key`.
+    fn expand_value(&self, key: &str, raw: &str, stack: &mut Vec<String>) -> Result<String, Error> {
+        let mut expanded = String::with_capacity(raw.len());
+        let mut rest = raw;
+
+        while let Some(pos) = rest.find('%') {
+            expanded.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            if let Some(after) = rest.strip_prefix('%') {
+                expanded.push('%');
+                rest = after;
+                continue;
+            }
+
+            let reference = rest
+                .strip_prefix('(')
+                .and_then(|after| after.split_once(")s"))
+                .ok_or_else(|| Error::ConfigParse {
+                    section: Some(self.title.to_string()),
+                    key: Some(key.to_string()),
+                    message: "Invalid '%' interpolation".to_string(),
+                });
+            let (name, after) = reference?;
+
+            if self.get(name).is_none() {
+                return Err(Error::UnresolvedReference {
+                    reference: format!("%({name})s"),
+                    section: Some(self.title.to_string()),
+                    key: key.to_string(),
+                });
+            }
+
+            expanded.push_str(&self.expand(name, stack)?);
+            rest = after;
+        }
+
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
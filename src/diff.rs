@@ -0,0 +1,105 @@
+use crate::Config;
+use std::collections::BTreeSet;
+
+/// A value that differs between two configs, as reported by
+/// [`Config::diff`]. `old` is `None` for added keys and `new` is `None`
+/// for removed ones.
+///
+/// `old` and `new` hold the value [`Config::get`] returns, which is the
+/// last one for repeated keys. `old_values` and `new_values` hold every
+/// value, as [`Config::get_all`] returns them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change {
+    pub section: Option<String>,
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub old_values: Vec<String>,
+    pub new_values: Vec<String>,
+}
+
+impl Change {
+    pub fn is_added(&self) -> bool {
+        self.old.is_none()
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.new.is_none()
+    }
+}
+
+/// The differences between two configs, ordered by section and key.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConfigDiff {
+    pub changes: Vec<Change>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether the value of `key` changed.
+    pub fn contains(&self, section: Option<&str>, key: &str) -> bool {
+        self.get(section, key).is_some()
+    }
+
+    pub fn get(&self, section: Option<&str>, key: &str) -> Option<&Change> {
+        self.changes
+            .iter()
+            .find(|c| c.section.as_deref() == section && c.key == key)
+    }
+
+    /// Whether any key in the section `title` changed.
+    pub fn touches_section(&self, title: &str) -> bool {
+        self.changes
+            .iter()
+            .any(|c| c.section.as_deref() == Some(title))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Change> {
+        self.changes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ConfigDiff {
+    type Item = &'a Change;
+    type IntoIter = std::slice::Iter<'a, Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+impl Config {
+    /// Lists the values that differ between this config and `other`, treating
+    /// this one as the old version. Keys that are repeated are compared by
+    /// all of their values, in order.
+    pub fn diff(&self, other: &Config) -> ConfigDiff {
+        let mut keys = BTreeSet::new();
+        for config in [self, other] {
+            keys.extend(config.general_values.keys().map(|k| (None, k.as_str())));
+            for (title, section) in &config.sections {
+                keys.extend(section.keys().map(|k| (Some(title.as_str()), k.as_str())));
+            }
+        }
+
+        let changes = keys
+            .into_iter()
+            .filter_map(|(section, key)| {
+                let old_values = self.get_all(section, key);
+                let new_values = other.get_all(section, key);
+                (old_values != new_values).then(|| Change {
+                    section: section.map(str::to_string),
+                    key: key.to_string(),
+                    old: old_values.last().cloned(),
+                    new: new_values.last().cloned(),
+                    old_values,
+                    new_values,
+                })
+            })
+            .collect();
+
+        ConfigDiff { changes }
+    }
+}
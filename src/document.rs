@@ -0,0 +1,689 @@
+use crate::{
+    atomic,
+    comment::{self, Comments},
+    inherit, options, Config, Error, ParseError, ParseOptions, WriteOptions,
+};
+use std::{fmt, fs, path::Path, str::FromStr};
+
+/// The indentation of continuation lines written for multi-line values.
+const MULTILINE_INDENT: &str = "    ";
+
+/// A single line of a [`ConfigDocument`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Line {
+    /// Blank lines, comments and anything else that isn't a section or key.
+    Other(String),
+    Section {
+        raw: String,
+        title: String,
+    },
+    /// `prefix` holds everything before the value, including the key, the
+    /// separator and its spacing, and `suffix` any trailing whitespace.
+    /// `continuation` holds the raw lines that continue a multi-line value.
+    Entry {
+        prefix: String,
+        key: String,
+        value: String,
+        suffix: String,
+        continuation: Vec<String>,
+    },
+}
+
+/// A lossless, line-based view of an INI file.
+///
+/// Unlike [`Config`], a `ConfigDocument` keeps comments, blank lines and the
+/// original order of sections and keys. Edits made with
+/// [`ConfigDocument::update`] and [`ConfigDocument::remove`] only rewrite the
+/// lines they touch, so hand-maintained files keep their layout when saved.
+///
+/// ```rust
+/// # use config_tools::ConfigDocument;
+/// let mut doc: ConfigDocument = "; Server settings\n[Server]\nport = 8080\n".parse().unwrap();
+/// doc.update(Some("Server"), "port", "9090");
+///
+/// assert_eq!(doc.to_string(), "; Server settings\n[Server]\nport = 9090\n");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConfigDocument {
+    pub(crate) lines: Vec<Line>,
+    newline: &'static str,
+    trailing_newline: bool,
+    multiline: bool,
+    /// Whether `[child : parent]` headers name the section `child`.
+    inheritance: bool,
+}
+
+impl ConfigDocument {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::load(path, e))?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Splits `text` into lines. This never fails; lines that aren't valid
+    /// INI are kept as they are and reported by [`ConfigDocument::to_config`].
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with(text, &ParseOptions::default())
+    }
+
+    /// Splits `text` into lines, treating indented lines after a key as part
+    /// of its value when [`ParseOptions::multiline`] is set.
+    pub fn parse_with(text: &str, options: &ParseOptions) -> Self {
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let mut lines: Vec<Line> = Vec::new();
+        let mut continued = false;
+
+        for raw in text.lines() {
+            let indented = raw.starts_with([' ', '\t']) && !raw.trim().is_empty();
+            if continued || (options.multiline && indented) {
+                // Blank lines inside a multi-line value belong to it.
+                let blanks = lines.iter().rev().take_while(|l| is_blank(l)).count();
+                let start = lines.len() - blanks;
+
+                if start > 0 && matches!(lines[start - 1], Line::Entry { .. }) {
+                    let mut blank_lines: Vec<String> = lines
+                        .drain(start..)
+                        .filter_map(|line| match line {
+                            Line::Other(raw) => Some(raw),
+                            _ => None,
+                        })
+                        .collect();
+                    blank_lines.push(raw.to_string());
+
+                    if let Some(Line::Entry { continuation, .. }) = lines.last_mut() {
+                        continuation.extend(blank_lines);
+                    }
+                    continued = ends_with_backslash(raw);
+                    continue;
+                }
+            }
+
+            let line = parse_line(raw);
+            continued = matches!(line, Line::Entry { .. }) && ends_with_backslash(raw);
+            lines.push(line);
+        }
+
+        Self {
+            lines,
+            newline,
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+            multiline: options.multiline,
+            inheritance: options.inheritance,
+        }
+    }
+
+    /// Builds a document laid out the same way [`Config::save`] writes files,
+    /// with comments on the lines above their keys and sections.
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_config_with(config, &WriteOptions::default())
+    }
+
+    /// Builds a document from a config using a specific INI dialect.
+    pub fn from_config_with(config: &Config, options: &WriteOptions) -> Self {
+        let mut doc = Self::parse("");
+        doc.newline = options.line_ending.as_str();
+        doc.multiline = options.multiline;
+        doc.inheritance = !config.parents.is_empty();
+
+        let titles = if options.preserve_order {
+            config.titles_in_order()
+        } else {
+            config.sections.keys().map(String::as_str).collect()
+        };
+
+        for key in keys(config, None, options) {
+            doc.push_values(config, None, key, options);
+        }
+
+        for title in titles {
+            doc.push_section(
+                &config.header(title),
+                config.section_comment(title),
+                options.comment_char,
+            );
+            for key in keys(config, Some(title), options) {
+                doc.push_values(config, Some(title), key, options);
+            }
+        }
+
+        doc
+    }
+
+    /// Saves the document atomically: it is written to a temporary file
+    /// and renamed over `path`, so a crash never leaves a truncated file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<&Self, Error> {
+        self.save_to(path.as_ref(), &WriteOptions::default())
+    }
+
+    pub(crate) fn save_to(&self, path: &Path, options: &WriteOptions) -> Result<&Self, Error> {
+        atomic::write_atomic(path, self.to_string().as_bytes(), options)
+            .map_err(|e| Error::create(path, e))?;
+        Ok(self)
+    }
+
+    /// Parses the document into a [`Config`], including its comments.
+    pub fn to_config(&self) -> Result<Config, Error> {
+        let options = ParseOptions {
+            multiline: self.multiline,
+            inheritance: self.inheritance,
+            ..ParseOptions::default()
+        };
+        Ok(Config::parse_ini(&self.to_string(), &options)?.1)
+    }
+
+    /// Finds the first line that is neither blank, a comment, a section
+    /// header nor a key, which the underlying parser would reject.
+    pub(crate) fn malformed_line(&self, text: &str) -> Option<ParseError> {
+        let mut number = 0;
+
+        for line in &self.lines {
+            number += 1;
+            match line {
+                Line::Other(raw) => {
+                    if let Some((column, message)) = malformed(raw) {
+                        return Some(ParseError::new(text, number, column, message));
+                    }
+                }
+                Line::Entry { continuation, .. } => number += continuation.len(),
+                Line::Section { .. } => {}
+            }
+        }
+
+        None
+    }
+
+    /// Collects the comment lines directly above each key and section
+    /// header. A blank line detaches a comment from what follows it.
+    pub(crate) fn comments(&self) -> Comments {
+        let mut comments = Comments::default();
+        let mut pending: Vec<&str> = Vec::new();
+        let mut section: Option<&str> = None;
+
+        for line in &self.lines {
+            match line {
+                Line::Other(raw) => match comment::comment_text(raw) {
+                    Some(text) => pending.push(text),
+                    None => pending.clear(),
+                },
+                Line::Section { title, .. } => {
+                    let title = self.title(title);
+                    if !pending.is_empty() {
+                        comments
+                            .sections
+                            .insert(title.to_string(), pending.join("\n"));
+                    }
+                    section = Some(title);
+                    pending.clear();
+                }
+                Line::Entry { key, .. } => {
+                    if !pending.is_empty() {
+                        comments.set(section, key, &pending.join("\n"));
+                    }
+                    pending.clear();
+                }
+            }
+        }
+
+        comments
+    }
+
+    /// Sets a value, rewriting only the line that holds it. Multi-line values
+    /// are replaced by a single line with `\n` escapes.
+    ///
+    /// If the key doesn't exist it is added after the last key of its
+    /// section, and a missing section is appended to the end of the document.
+    pub fn update(&mut self, section: Option<&str>, key: &str, value: &str) -> &mut Self {
+        let value = escape(value);
+
+        if let Some(index) = self.find_entry(section, key) {
+            self.set_value(index, value);
+            return self;
+        }
+
+        self.insert_entry(section, key, value);
+        self
+    }
+
+    /// Adds another line for `key` after the last key of its section,
+    /// keeping any lines it already has.
+    pub fn add(&mut self, section: Option<&str>, key: &str, value: &str) -> &mut Self {
+        self.insert_entry(section, key, escape(value));
+        self
+    }
+
+    fn insert_entry(&mut self, section: Option<&str>, key: &str, value: String) {
+        let entry = self.new_entry(key, value);
+
+        let index = match section {
+            Some(title) => match self.section_range(title) {
+                Some(range) => self.insertion_point(range),
+                None => {
+                    self.push_section(title, None, ';');
+                    self.lines.len()
+                }
+            },
+            None => {
+                let range = 0..self.section_start(0);
+                self.insertion_point(range)
+            }
+        };
+
+        self.lines.insert(index, entry);
+    }
+
+    /// Removes every line holding `key` in the given section.
+    pub fn remove(&mut self, section: Option<&str>, key: &str) -> &mut Self {
+        let ranges = self.ranges(section);
+        let mut index = 0;
+
+        self.lines.retain(|line| {
+            let in_section = ranges.iter().any(|r| r.contains(&index));
+            index += 1;
+            !(in_section && matches!(line, Line::Entry { key: k, .. } if k == key))
+        });
+
+        self
+    }
+
+    /// Removes a section header along with every line up to the next section.
+    pub fn remove_section(&mut self, title: &str) -> &mut Self {
+        while let Some(range) = self.section_range(title) {
+            self.lines.drain(range.start - 1..range.end);
+        }
+
+        self
+    }
+
+    /// Brings the document in line with `config`, touching only the lines
+    /// whose values differ and keeping everything else as it is.
+    pub fn apply(&mut self, config: &Config) -> Result<&mut Self, Error> {
+        let current = self.to_config()?;
+
+        for key in current.general_values.keys() {
+            if !config.general_values.contains_key(key) {
+                self.remove(None, key);
+            }
+        }
+
+        for (title, section) in &current.sections {
+            match config.sections.get(title) {
+                None => {
+                    self.remove_section(title);
+                }
+                Some(new) => {
+                    for key in section.keys().filter(|k| !new.contains_key(*k)) {
+                        self.remove(Some(title), key);
+                    }
+                }
+            }
+        }
+
+        for key in config.general_values.keys() {
+            self.apply_values(&current, config, None, key);
+        }
+
+        for (title, section) in &config.sections {
+            if !current.sections.contains_key(title) && self.section_range(title).is_none() {
+                self.push_section(&config.header(title), config.section_comment(title), ';');
+            }
+
+            for key in section.keys() {
+                self.apply_values(&current, config, Some(title), key);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Rewrites the lines of one key if its values differ between the configs.
+    fn apply_values(
+        &mut self,
+        current: &Config,
+        config: &Config,
+        section: Option<&str>,
+        key: &str,
+    ) {
+        let values = config.raw_values(section, key);
+        let old = current.raw_values(section, key);
+        if values == old {
+            return;
+        }
+
+        let indices: Vec<usize> = self
+            .ranges(section)
+            .into_iter()
+            .flatten()
+            .filter(|&i| matches!(&self.lines[i], Line::Entry { key: k, .. } if k == key))
+            .collect();
+
+        if indices.is_empty() {
+            for value in values {
+                self.add(section, key, value);
+            }
+            return;
+        }
+
+        // Existing lines are rewritten in place, surplus lines dropped and
+        // extra values added right after the last line that was kept.
+        for (&index, value) in indices.iter().zip(&values) {
+            self.set_value(index, escape(value));
+        }
+
+        for &index in indices.iter().skip(values.len()).rev() {
+            self.lines.remove(index);
+        }
+
+        let last = indices[values.len().min(indices.len()) - 1];
+        for (offset, value) in values.iter().skip(indices.len()).enumerate() {
+            let entry = self.new_entry(key, escape(value));
+            self.lines.insert(last + 1 + offset, entry);
+        }
+    }
+
+    fn set_value(&mut self, index: usize, value: String) {
+        if let Line::Entry {
+            value: old,
+            continuation,
+            ..
+        } = &mut self.lines[index]
+        {
+            *old = value;
+            continuation.clear();
+        }
+    }
+
+    fn new_entry(&self, key: &str, value: String) -> Line {
+        Line::Entry {
+            prefix: format!("{key}{}", self.separator()),
+            key: key.to_string(),
+            value,
+            suffix: String::new(),
+            continuation: Vec::new(),
+        }
+    }
+
+    fn push_section(&mut self, title: &str, comment: Option<&str>, marker: char) {
+        if self.lines.last().is_some_and(|l| !is_blank(l)) {
+            self.lines.push(Line::Other(String::new()));
+        }
+
+        self.push_comment(comment, marker);
+        self.lines.push(Line::Section {
+            raw: format!("[{title}]"),
+            title: title.to_string(),
+        });
+    }
+
+    /// Pushes one line for each value of a key, with its comment above the first.
+    fn push_values(
+        &mut self,
+        config: &Config,
+        section: Option<&str>,
+        key: &str,
+        options: &WriteOptions,
+    ) {
+        let mut comment = config.comment(section, key);
+        for value in config.raw_values(section, key) {
+            self.push_entry(comment.take(), key, value, options);
+        }
+    }
+
+    fn push_entry(
+        &mut self,
+        comment: Option<&str>,
+        key: &str,
+        value: &str,
+        options: &WriteOptions,
+    ) {
+        let escape = |value: &str| {
+            if options.escape {
+                escape(value)
+            } else {
+                value.to_string()
+            }
+        };
+
+        // Multi-line values continue on indented lines, as configparser
+        // writes them.
+        let (value, continuation) = match value.split_once('\n') {
+            Some((first, rest)) if options.multiline => {
+                let lines = rest.split('\n').map(|line| match line {
+                    "" => String::new(),
+                    line => format!("{MULTILINE_INDENT}{}", escape(line)),
+                });
+                (escape(first), lines.collect())
+            }
+            _ => (escape(value), Vec::new()),
+        };
+
+        self.push_comment(comment, options.comment_char);
+        self.lines.push(Line::Entry {
+            prefix: format!("{key}{}", options.separator()),
+            key: key.to_string(),
+            value,
+            suffix: String::new(),
+            continuation,
+        });
+    }
+
+    fn push_comment(&mut self, comment: Option<&str>, marker: char) {
+        let lines = comment
+            .into_iter()
+            .flat_map(|text| comment::comment_lines(text, marker));
+        self.lines.extend(lines.map(Line::Other));
+    }
+
+    /// Uses the separator style of the first key in the document, or `=`.
+    fn separator(&self) -> String {
+        self.lines
+            .iter()
+            .find_map(|line| match line {
+                Line::Entry { prefix, key, .. } => {
+                    Some(prefix.trim_start()[key.len()..].to_string())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| "=".to_string())
+    }
+
+    fn find_entry(&self, section: Option<&str>, key: &str) -> Option<usize> {
+        self.ranges(section)
+            .into_iter()
+            .flatten()
+            .rfind(|&i| matches!(&self.lines[i], Line::Entry { key: k, .. } if k == key))
+    }
+
+    /// The index of the next section header at or after `from`.
+    fn section_start(&self, from: usize) -> usize {
+        (from..self.lines.len())
+            .find(|&i| matches!(self.lines[i], Line::Section { .. }))
+            .unwrap_or(self.lines.len())
+    }
+
+    /// The body of every occurrence of a section, or of the general section.
+    fn ranges(&self, section: Option<&str>) -> Vec<std::ops::Range<usize>> {
+        let Some(title) = section else {
+            let general = 0..self.section_start(0);
+            return Vec::from([general]);
+        };
+
+        (0..self.lines.len())
+            .filter(|&i| matches!(&self.lines[i], Line::Section { title: t, .. } if self.title(t) == title))
+            .map(|i| i + 1..self.section_start(i + 1))
+            .collect()
+    }
+
+    /// The section a header line belongs to.
+    fn title<'a>(&self, title: &'a str) -> &'a str {
+        if self.inheritance {
+            inherit::split_header(title).0
+        } else {
+            title
+        }
+    }
+
+    /// The body of the last occurrence of a section.
+    fn section_range(&self, title: &str) -> Option<std::ops::Range<usize>> {
+        self.ranges(Some(title)).pop()
+    }
+
+    /// Where to add a key to a section body: after its last key, or before
+    /// the blank lines that separate it from the next section.
+    fn insertion_point(&self, range: std::ops::Range<usize>) -> usize {
+        if let Some(last) = range
+            .clone()
+            .rev()
+            .find(|&i| matches!(self.lines[i], Line::Entry { .. }))
+        {
+            return last + 1;
+        }
+
+        let mut index = range.end;
+        while index > range.start && is_blank(&self.lines[index - 1]) {
+            index -= 1;
+        }
+        index
+    }
+}
+
+impl fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.newline)?;
+            }
+
+            match line {
+                Line::Other(raw) | Line::Section { raw, .. } => f.write_str(raw)?,
+                Line::Entry {
+                    prefix,
+                    value,
+                    suffix,
+                    continuation,
+                    ..
+                } => {
+                    write!(f, "{prefix}{value}{suffix}")?;
+                    for line in continuation {
+                        write!(f, "{}{line}", self.newline)?;
+                    }
+                }
+            }
+        }
+
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(self.newline)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ConfigDocument {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+/// The keys of a section, sorted or in the order `options` asks for.
+fn keys<'a>(config: &'a Config, section: Option<&str>, options: &WriteOptions) -> Vec<&'a str> {
+    if options.preserve_order {
+        return config.keys_in_order(section);
+    }
+
+    let keys = match section {
+        Some(title) => config.sections.get(title),
+        None => Some(&config.general_values),
+    };
+    keys.into_iter()
+        .flat_map(|k| k.keys())
+        .map(String::as_str)
+        .collect()
+}
+
+fn parse_line(raw: &str) -> Line {
+    let trimmed = raw.trim();
+
+    if trimmed.is_empty() || trimmed.starts_with([';', '#']) {
+        return Line::Other(raw.to_string());
+    }
+
+    if let Some(title) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        return Line::Section {
+            raw: raw.to_string(),
+            title: title.trim().to_string(),
+        };
+    }
+
+    match raw.find(['=', ':']) {
+        Some(pos) => {
+            let rest = &raw[pos + 1..];
+            let value_start = pos + 1 + (rest.len() - rest.trim_start().len());
+            let value_end = pos + 1 + rest.trim_end().len();
+            let value_end = value_end.max(value_start);
+
+            Line::Entry {
+                prefix: raw[..value_start].to_string(),
+                key: options::unescape_key(raw[..pos].trim()).to_string(),
+                value: raw[value_start..value_end].to_string(),
+                suffix: raw[value_end..].to_string(),
+                continuation: Vec::new(),
+            }
+        }
+        None => Line::Other(raw.to_string()),
+    }
+}
+
+/// Explains why a line that isn't a section or key can't be parsed, with
+/// the column to point at.
+pub(crate) fn malformed(raw: &str) -> Option<(usize, String)> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() || trimmed.starts_with([';', '#']) {
+        return None;
+    }
+
+    if trimmed.starts_with('[') {
+        let column = raw.trim_end().chars().count() + 1;
+        return Some((
+            column,
+            "Expected ']' to close the section header".to_string(),
+        ));
+    }
+
+    let column = raw.chars().take_while(|c| c.is_whitespace()).count() + 1;
+    Some((column, format!("Expected '=' or ':' after '{trimmed}'")))
+}
+
+/// Whether a line ends with an unescaped `\`, which joins it with the next.
+fn ends_with_backslash(raw: &str) -> bool {
+    let trailing = raw.len() - raw.trim_end_matches('\\').len();
+    trailing % 2 == 1
+}
+
+fn is_blank(line: &Line) -> bool {
+    matches!(line, Line::Other(raw) if raw.trim().is_empty())
+}
+
+/// Escapes a value the same way `rust-ini` does when writing files.
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\0' => escaped.push_str("\\0"),
+            '\x07' => escaped.push_str("\\a"),
+            '\x08' => escaped.push_str("\\b"),
+            '\x0b' => escaped.push_str("\\v"),
+            '\x0c' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\x01'..='\x1f' | '\x7f' => escaped.push_str(&format!("\\x{:04x}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
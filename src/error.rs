@@ -1,27 +1,272 @@
-use std::fmt;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
 /// Represents errors that may occur during loading, saving, or parsing
 /// configuration files.
 ///
 /// This includes I/O errors from file operations as well as user-facing
-/// errors like missing keys or invalid values.
+/// errors like missing keys or invalid values. Variants carry the file,
+/// section or key they concern, and I/O errors are available through
+/// [`std::error::Error::source`].
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
-    AlreadyExists,
-    ConfigParse(String),
-    NotFound,
-    ConfigLoad(ini::Error),
-    ConfigCreation(std::io::Error),
+    AlreadyExists {
+        section: Option<String>,
+        key: String,
+    },
+    ConfigParse {
+        section: Option<String>,
+        key: Option<String>,
+        message: String,
+    },
+    /// The config was loaded with [`ParseOptions::includes`] and merges
+    /// the values of the `includes` files, so saving it to `path` would
+    /// copy them into that file and lose the directives.
+    ///
+    /// [`ParseOptions::includes`]: crate::ParseOptions::includes
+    HasIncludes {
+        path: PathBuf,
+        includes: Vec<PathBuf>,
+    },
+    IncludeCycle(Vec<PathBuf>),
+    InheritanceCycle(Vec<String>),
+    /// A file holding the secret `keys` can be read by other users. See
+    /// [`ParseOptions::secrets`](crate::ParseOptions::secrets).
+    InsecurePermissions {
+        path: PathBuf,
+        mode: u32,
+        keys: Vec<String>,
+    },
+    InterpolationCycle(Vec<String>),
+    /// The file changed on disk since the config was loaded. `conflicts`
+    /// lists the keys that couldn't be merged by [`Config::save_merged`].
+    ///
+    /// [`Config::save_merged`]: crate::Config::save_merged
+    Modified {
+        path: PathBuf,
+        conflicts: Vec<String>,
+    },
+    NotFound {
+        section: Option<String>,
+        key: String,
+    },
+    Parse(ParseError),
+    Strict(ParseError),
+    UnresolvedReference {
+        reference: String,
+        section: Option<String>,
+        key: String,
+    },
+    ConfigLoad {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    ConfigCreation {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::AlreadyExists => write!(f, "The key already exists"),
-            Error::ConfigParse(e) => write!(f, "Failed to parse config: {e}"),
-            Error::NotFound => write!(f, "The key was not found"),
-            Error::ConfigLoad(e) => write!(f, "Failed to load config file: {e:?}"),
-            Error::ConfigCreation(e) => write!(f, "Failed to create config file: {e:?}"),
+            Error::AlreadyExists { section, key } => {
+                write!(f, "The key '{}' already exists", location(section, key))
+            }
+            Error::ConfigParse {
+                section,
+                key,
+                message,
+            } => {
+                write!(f, "Failed to parse config: {message}")?;
+                match (section, key) {
+                    (_, Some(key)) => write!(f, " in '{}'", location(section, key)),
+                    (Some(section), None) => write!(f, " in section '{section}'"),
+                    (None, None) => Ok(()),
+                }
+            }
+            Error::HasIncludes { path, includes } => {
+                let includes: Vec<_> = includes.iter().map(|p| p.display().to_string()).collect();
+                write!(
+                    f,
+                    "Refusing to save '{}': the config includes values from {}",
+                    path.display(),
+                    includes.join(", ")
+                )
+            }
+            Error::IncludeCycle(chain) => {
+                let chain: Vec<_> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Include cycle detected: {}", chain.join(" -> "))
+            }
+            Error::InheritanceCycle(chain) => {
+                write!(f, "Inheritance cycle detected: {}", chain.join(" -> "))
+            }
+            Error::InsecurePermissions { path, mode, keys } => write!(
+                f,
+                "The config file '{}' holds secrets ({}) but is readable by other users (mode {mode:o})",
+                path.display(),
+                keys.join(", ")
+            ),
+            Error::InterpolationCycle(chain) => {
+                write!(f, "Interpolation cycle detected: {}", chain.join(" -> "))
+            }
+            Error::Modified { path, conflicts } => {
+                write!(
+                    f,
+                    "The config file '{}' was changed since it was loaded",
+                    path.display()
+                )?;
+                if !conflicts.is_empty() {
+                    write!(f, "; conflicting keys: {}", conflicts.join(", "))?;
+                }
+                Ok(())
+            }
+            Error::NotFound { section, key } => {
+                write!(f, "The key '{}' was not found", location(section, key))
+            }
+            Error::Parse(e) => write!(f, "Failed to parse config: {e}"),
+            Error::Strict(e) => write!(f, "Rejected by strict mode: {e}"),
+            Error::UnresolvedReference {
+                reference,
+                section,
+                key,
+            } => write!(
+                f,
+                "Unresolved reference {reference} in '{}'",
+                location(section, key)
+            ),
+            Error::ConfigLoad { path, source } => match path {
+                Some(path) => write!(
+                    f,
+                    "Failed to load config file '{}': {source}",
+                    path.display()
+                ),
+                None => write!(f, "Failed to load config: {source}"),
+            },
+            Error::ConfigCreation { path, source } => match path {
+                Some(path) => write!(
+                    f,
+                    "Failed to write config file '{}': {source}",
+                    path.display()
+                ),
+                None => write!(f, "Failed to write config: {source}"),
+            },
         }
     }
 }
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ConfigLoad { source, .. } | Error::ConfigCreation { source, .. } => Some(source),
+            Error::Parse(e) | Error::Strict(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a key as `Section:key`, or just `key` in the general section.
+fn location(section: &Option<String>, key: &str) -> String {
+    match section {
+        Some(section) => format!("{section}:{key}"),
+        None => key.to_string(),
+    }
+}
+
+impl Error {
+    pub(crate) fn load(path: &Path, source: io::Error) -> Self {
+        Error::ConfigLoad {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    pub(crate) fn create(path: &Path, source: io::Error) -> Self {
+        Error::ConfigCreation {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    /// Records the file a parse error came from.
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        if let Error::Parse(e) | Error::Strict(e) = &mut self {
+            e.path.get_or_insert_with(|| path.to_path_buf());
+        }
+        self
+    }
+}
+
+/// Where and why a config failed to parse.
+///
+/// `line` and `column` start at 1. [`ParseError::render`] formats the
+/// error with the offending line and a caret under the column:
+///
+/// ```text
+/// error: Duplicate key 'port' (first defined on line 2)
+///  --> config.ini:3:1
+///   |
+/// 3 | port = 8080
+///   | ^
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub line_text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Builds an error for a position in `text`, copying the line it is on.
+    pub(crate) fn new(text: &str, line: usize, column: usize, message: impl Into<String>) -> Self {
+        let line_text = text.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+
+        Self {
+            path: None,
+            line,
+            column,
+            line_text: line_text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Formats the error like a compiler diagnostic, showing the offending
+    /// line with a caret under the column.
+    pub fn render(&self) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let location = match &self.path {
+            Some(path) => format!("{}:{}:{}", path.display(), self.line, self.column),
+            None => format!("{}:{}", self.line, self.column),
+        };
+
+        // Tabs are kept so that the caret lines up with the text above it.
+        let padding: String = self
+            .line_text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        format!(
+            "error: {}\n{gutter}--> {location}\n{gutter} |\n{number} | {}\n{gutter} | {padding}^\n",
+            self.message, self.line_text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
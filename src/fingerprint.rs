@@ -0,0 +1,314 @@
+use crate::{include, lock::FileLock, Config, Error, ParseOptions, WriteOptions};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// Identifies the contents of a file at the time it was read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Fingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Fingerprint {
+    fn new(meta: &fs::Metadata, contents: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Whether `path` still holds the contents this fingerprint was taken
+    /// of. The size and modification time are checked first, and the file is
+    /// only read again when they differ.
+    fn matches(&self, path: &Path) -> io::Result<bool> {
+        let meta = match fs::metadata(path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        if meta.len() == self.len && meta.modified().ok() == self.modified {
+            return Ok(true);
+        }
+
+        let contents = fs::read(path)?;
+        Ok(Fingerprint::new(&meta, &contents).hash == self.hash)
+    }
+}
+
+/// The file a [`Config`] was loaded from, kept so that later saves can tell
+/// whether someone else changed it in the meantime.
+#[derive(Debug)]
+pub(crate) struct Origin {
+    path: PathBuf,
+    fingerprint: Fingerprint,
+    text: String,
+    options: ParseOptions,
+}
+
+impl Origin {
+    /// Reads `path`, taking the metadata first so that a change made while
+    /// reading is noticed by the next check.
+    pub(crate) fn read(path: &Path, options: &ParseOptions) -> io::Result<Arc<Self>> {
+        let meta = fs::metadata(path)?;
+        let text = fs::read_to_string(path)?;
+
+        Ok(Arc::new(Self {
+            path: path.to_path_buf(),
+            fingerprint: Fingerprint::new(&meta, text.as_bytes()),
+            text,
+            options: options.clone(),
+        }))
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The config as it was loaded, before any changes made in memory.
+    fn base(&self) -> Result<Config, Error> {
+        let (_, mut base) = Config::parse_ini(&self.text, &self.options)?;
+        if self.options.includes {
+            let path = self
+                .path
+                .canonicalize()
+                .map_err(|e| Error::load(&self.path, e))?;
+            base = include::follow(base, &path, &self.options, &mut Vec::new())?;
+        }
+        Ok(base)
+    }
+}
+
+impl Config {
+    /// Saves to `path` unless the file changed on disk since this config was
+    /// loaded from it, in which case [`Error::Modified`] is returned and the
+    /// file is left alone.
+    ///
+    /// Configs that weren't loaded from `path` are saved unconditionally.
+    /// After saving, later checks compare against the file just written.
+    pub fn save_checked<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, Error> {
+        self.save_checked_with(path, &WriteOptions::default())
+    }
+
+    /// [`Config::save_checked`] using a specific INI dialect. With
+    /// [`WriteOptions::lock`], the lock is held from the check until the
+    /// file is replaced.
+    pub fn save_checked_with<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &WriteOptions,
+    ) -> Result<&mut Self, Error> {
+        let path = path.as_ref();
+        self.check_includes(path)?;
+        let (_lock, options) = lock(path, options)?;
+
+        if let Some(origin) = self.origin_of(path) {
+            if !origin
+                .fingerprint
+                .matches(path)
+                .map_err(|e| Error::load(path, e))?
+            {
+                return Err(Error::Modified {
+                    path: path.to_path_buf(),
+                    conflicts: Vec::new(),
+                });
+            }
+        }
+
+        self.save_and_track(path, &options)
+    }
+
+    /// Saves to `path`, first merging in changes made to the file since this
+    /// config was loaded from it.
+    ///
+    /// Each value is merged three ways between the file as it was loaded,
+    /// the file on disk and this config: whichever side changed a value
+    /// wins. Values changed differently on both sides are reported as
+    /// conflicts in [`Error::Modified`], and nothing is saved. Comments are
+    /// merged the same way, except that this config's comment wins when
+    /// both sides changed it.
+    pub fn save_merged<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, Error> {
+        self.save_merged_with(path, &WriteOptions::default())
+    }
+
+    /// [`Config::save_merged`] using a specific INI dialect. With
+    /// [`WriteOptions::lock`], the lock is held while the file is read
+    /// back, merged and replaced.
+    pub fn save_merged_with<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &WriteOptions,
+    ) -> Result<&mut Self, Error> {
+        let path = path.as_ref();
+        self.check_includes(path)?;
+        let (_lock, options) = lock(path, options)?;
+
+        let Some(origin) = self.origin_of(path) else {
+            return self.save_and_track(path, &options);
+        };
+
+        if origin
+            .fingerprint
+            .matches(path)
+            .map_err(|e| Error::load(path, e))?
+        {
+            return self.save_and_track(path, &options);
+        }
+
+        let base = origin.base()?;
+        // The exclusive lock is already held, if one was asked for.
+        let read_options = ParseOptions {
+            lock: false,
+            ..origin.options.clone()
+        };
+        let theirs = match Config::load_with(path, &read_options) {
+            Ok(theirs) => theirs,
+            Err(Error::ConfigLoad { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                Config::default()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let conflicts = self.merge_three_way(&base, &theirs);
+        if !conflicts.is_empty() {
+            return Err(Error::Modified {
+                path: path.to_path_buf(),
+                conflicts,
+            });
+        }
+
+        self.save_and_track(path, &options)
+    }
+
+    fn origin_of(&self, path: &Path) -> Option<Arc<Origin>> {
+        self.origin
+            .clone()
+            .filter(|origin| same_file(&origin.path, path))
+    }
+
+    fn save_and_track(&mut self, path: &Path, options: &WriteOptions) -> Result<&mut Self, Error> {
+        self.save_with(path, options)?;
+
+        let options = self
+            .origin
+            .as_ref()
+            .map(|o| o.options.clone())
+            .unwrap_or_default();
+        self.origin = Origin::read(path, &options).ok();
+        Ok(self)
+    }
+
+    /// Applies the changes made in `theirs` since `base` to this config,
+    /// returning the keys both sides changed differently.
+    fn merge_three_way(&mut self, base: &Config, theirs: &Config) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        let mut all = keys(base);
+        all.extend(keys(theirs));
+        all.extend(keys(self));
+
+        for (section, key) in &all {
+            let section = section.as_deref();
+            self.merge_comment(base, theirs, section, key);
+
+            let (old, new) = (
+                base.raw_values(section, key),
+                theirs.raw_values(section, key),
+            );
+            let ours = self.raw_values(section, key);
+
+            if new == old || new == ours {
+                continue;
+            }
+            if ours != old {
+                conflicts.push(match section {
+                    Some(section) => format!("{section}:{key}"),
+                    None => key.clone(),
+                });
+                continue;
+            }
+
+            // Replace the values in place, keeping the key's position.
+            match new.split_first() {
+                Some((first, rest)) => {
+                    self.update(section, key, first);
+                    for value in rest {
+                        self.add(section, key, value);
+                    }
+                }
+                None => {
+                    self.remove(section, key);
+                }
+            }
+        }
+
+        // Drop sections the file no longer has once all their keys are gone.
+        for title in base.sections.keys() {
+            let emptied = self.sections.get(title).is_some_and(|s| s.is_empty());
+            if emptied && !theirs.sections.contains_key(title) {
+                self.remove_section(title);
+            }
+        }
+
+        conflicts
+    }
+
+    /// Takes the comment of a key from `theirs` if only they changed it.
+    fn merge_comment(&mut self, base: &Config, theirs: &Config, section: Option<&str>, key: &str) {
+        let old = base.comment(section, key);
+        let new = theirs.comment(section, key);
+        if new == old || self.comment(section, key) != old {
+            return;
+        }
+
+        match new {
+            Some(text) => {
+                self.comments.set(section, key, text);
+            }
+            None => self.comments.remove(section, key),
+        }
+    }
+}
+
+/// Takes the exclusive lock [`WriteOptions::lock`] asks for, returning the
+/// options to save with while it is held.
+fn lock(path: &Path, options: &WriteOptions) -> Result<(Option<FileLock>, WriteOptions), Error> {
+    let lock = options
+        .lock
+        .then(|| FileLock::exclusive(path))
+        .transpose()?;
+    let options = WriteOptions {
+        lock: false,
+        ..options.clone()
+    };
+    Ok((lock, options))
+}
+
+fn keys(config: &Config) -> BTreeSet<(Option<String>, String)> {
+    let general = config.general_values.keys().map(|k| (None, k.clone()));
+    let sections = config.sections.iter().flat_map(|(title, section)| {
+        section
+            .keys()
+            .map(move |k| (Some(title.clone()), k.clone()))
+    });
+
+    general.chain(sections).collect()
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
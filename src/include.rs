@@ -0,0 +1,143 @@
+use crate::{Config, Error, ParseOptions};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const INCLUDE: &str = "include";
+const INCLUDE_DIR: &str = "include_dir";
+
+impl Config {
+    /// Loads a config file and follows its `include = path` and
+    /// `include_dir = dir` directives.
+    ///
+    /// Directives are read from the general section and may be repeated.
+    /// Relative paths are resolved against the directory of the including
+    /// file. Included files are merged over the including file in the order
+    /// they are listed, and `include_dir` merges every `*.ini` file in the
+    /// directory in lexical order. The directives themselves are removed from
+    /// the result. Circular includes fail with [`Error::IncludeCycle`].
+    ///
+    /// Saving the result would write every included value into one file, so
+    /// [`Config::save`] and the other save methods refuse it with
+    /// [`Error::HasIncludes`] when any file was included.
+    ///
+    /// This is [`Config::load_with`] with [`ParseOptions::includes`] set.
+    pub fn load_with_includes<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::load_with(path, &include_options())
+    }
+
+    /// Merges every `*.ini` file in a directory in lexical order, following
+    /// includes in each of them. This suits `conf.d`-style drop-in folders.
+    /// Like configs with includes, the result can't be saved.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let options = include_options();
+        let mut config = Config::default();
+
+        for file in ini_files(dir.as_ref())? {
+            let included = load_recursive(&file, &options, &mut Vec::new())?;
+            config.includes.push(file);
+            config.merge(included);
+        }
+
+        config.check_parents()?;
+        Ok(config)
+    }
+
+    /// Returns the files merged into this config by `include` and
+    /// `include_dir` directives or [`Config::load_dir`], in the order they
+    /// were read.
+    pub fn included_files(&self) -> &[PathBuf] {
+        &self.includes
+    }
+
+    /// Refuses to save a config that merged in included files.
+    pub(crate) fn check_includes(&self, path: &Path) -> Result<(), Error> {
+        if self.includes.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::HasIncludes {
+            path: path.to_path_buf(),
+            includes: self.includes.clone(),
+        })
+    }
+}
+
+fn include_options() -> ParseOptions {
+    ParseOptions {
+        includes: true,
+        ..ParseOptions::default()
+    }
+}
+
+/// Reads `path` and every file it includes with the same options. `stack`
+/// holds the files currently being read, to detect cycles.
+pub(crate) fn load_recursive(
+    path: &Path,
+    options: &ParseOptions,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Config, Error> {
+    let canonical = path.canonicalize().map_err(|e| Error::load(path, e))?;
+
+    if let Some(start) = stack.iter().position(|p| *p == canonical) {
+        let mut chain = stack[start..].to_vec();
+        chain.push(canonical);
+        return Err(Error::IncludeCycle(chain));
+    }
+
+    let config = Config::read(&canonical, options)?;
+    follow(config, &canonical, options, stack)
+}
+
+/// Merges the files included by `config`, which was read from `path`, over
+/// it and removes the directives.
+pub(crate) fn follow(
+    mut config: Config,
+    path: &Path,
+    options: &ParseOptions,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Config, Error> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut includes: Vec<PathBuf> = config
+        .raw_values(None, INCLUDE)
+        .into_iter()
+        .map(|p| base.join(p))
+        .collect();
+    let dirs: Vec<PathBuf> = config
+        .raw_values(None, INCLUDE_DIR)
+        .into_iter()
+        .map(|d| base.join(d))
+        .collect();
+
+    for dir in dirs {
+        includes.extend(ini_files(&dir)?);
+    }
+    config.remove(None, INCLUDE);
+    config.remove(None, INCLUDE_DIR);
+
+    stack.push(path.to_path_buf());
+    for include in includes {
+        let included = load_recursive(&include, options, stack)?;
+        config.includes.push(include);
+        config.merge(included);
+    }
+    stack.pop();
+
+    Ok(config)
+}
+
+fn ini_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = fs::read_dir(dir).map_err(|e| Error::load(dir, e))?;
+    let mut files = Vec::new();
+
+    for entry in entries {
+        let path = entry.map_err(|e| Error::load(dir, e))?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "ini") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
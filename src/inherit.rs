@@ -0,0 +1,203 @@
+use crate::{Config, Error, DEFAULT_SECTION};
+use std::{borrow::Cow, collections::BTreeMap};
+
+/// The key naming a section's parent, as in `inherits = db.primary`.
+pub(crate) const INHERITS: &str = "inherits";
+
+impl Config {
+    /// Copies each parent's keys into the sections that inherit from it, so
+    /// that [`Config::sections`] holds every key and the config no longer
+    /// depends on its parents.
+    ///
+    /// Parents are those read with [`ParseOptions::inheritance`], and those
+    /// named by an `inherits = parent` key, which lets configs built by hand
+    /// use inheritance too. Keys set on the child win over inherited ones,
+    /// and chains of any depth are followed. Saving a resolved config writes
+    /// every inherited key into each child.
+    ///
+    /// [`ParseOptions::inheritance`]: crate::ParseOptions::inheritance
+    pub fn resolve_inheritance(&mut self) -> Result<&mut Self, Error> {
+        for (title, section) in self.sections.iter_mut() {
+            if let Some(parent) = section.remove(INHERITS) {
+                self.multi.remove(Some(title), INHERITS);
+                self.order.remove(Some(title), INHERITS);
+                self.parents.insert(title.clone(), parent);
+            }
+        }
+        self.check_parents()?;
+
+        let titles: Vec<String> = self.parents.keys().cloned().collect();
+        for title in titles {
+            for ancestor in self.ancestors(&title) {
+                let inherited: Vec<(String, String)> = self.sections[&ancestor]
+                    .iter()
+                    .filter(|(key, _)| !self.sections[&title].contains_key(*key))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+
+                for (key, value) in inherited {
+                    if let Some(values) = self.multi.get(Some(&ancestor), &key).cloned() {
+                        *self.multi.entry(Some(&title), &key) = values;
+                    }
+                    self.insert(Some(&title), &key, &value);
+                }
+            }
+        }
+
+        self.parents.clear();
+        Ok(self)
+    }
+
+    /// A section with the keys it inherits, nearest parent first, and the
+    /// `[DEFAULT]` keys after [`Config::apply_configparser`]. Borrowed when
+    /// the section has neither.
+    pub(crate) fn resolved_section(
+        &self,
+        title: &str,
+    ) -> Option<Cow<'_, BTreeMap<String, String>>> {
+        let own = self.sections.get(title)?;
+        let fallback = self.fallback(title).map(str::to_string);
+        let sources: Vec<String> = self.ancestors(title).into_iter().chain(fallback).collect();
+        if sources.is_empty() {
+            return Some(Cow::Borrowed(own));
+        }
+
+        let mut section = own.clone();
+        for source in sources {
+            if let Some(parent) = self.sections.get(&source) {
+                for (key, value) in parent {
+                    if !section.contains_key(key) {
+                        section.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+
+        Some(Cow::Owned(section))
+    }
+
+    /// The section a key is read from: `title` itself, the nearest parent
+    /// that sets it, or `[DEFAULT]`.
+    pub(crate) fn defining_section<'a>(&'a self, title: &'a str, key: &str) -> &'a str {
+        let mut current = title;
+
+        // Bounded, since a parent can be removed or replaced after loading.
+        for _ in 0..=self.parents.len() {
+            if self
+                .sections
+                .get(current)
+                .is_some_and(|s| s.contains_key(key))
+            {
+                return current;
+            }
+            match self.parents.get(current) {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+
+        match self.fallback(title) {
+            Some(fallback) if self.sections[fallback].contains_key(key) => fallback,
+            _ => title,
+        }
+    }
+
+    /// `[DEFAULT]`, if it provides fallbacks for the existing section `title`.
+    fn fallback(&self, title: &str) -> Option<&'static str> {
+        let applies = self.configparser
+            && title != DEFAULT_SECTION
+            && self.sections.contains_key(title)
+            && self.sections.contains_key(DEFAULT_SECTION);
+        applies.then_some(DEFAULT_SECTION)
+    }
+
+    /// The section title written for `title`, as `child : parent` if it
+    /// inherits.
+    pub(crate) fn header(&self, title: &str) -> String {
+        match self.parents.get(title) {
+            Some(parent) => format!("{title} : {parent}"),
+            None => title.to_string(),
+        }
+    }
+
+    /// Moves `[child : parent]` headers and `inherits` keys of a freshly
+    /// parsed file into the parent links.
+    pub(crate) fn link_parents(&mut self) {
+        let sections = std::mem::take(&mut self.sections);
+
+        for (title, mut section) in sections {
+            let (child, header_parent) = split_header(&title);
+            let child = child.to_string();
+
+            if child != title {
+                if let Some(values) = self.multi.sections.remove(&title) {
+                    self.multi.sections.insert(child.clone(), values);
+                }
+                self.order.rename_section(&title, &child);
+            }
+
+            let key_parent = section.remove(INHERITS);
+            if key_parent.is_some() {
+                self.multi.remove(Some(&child), INHERITS);
+                self.order.remove(Some(&child), INHERITS);
+            }
+            if let Some(parent) = header_parent.map(str::to_string).or(key_parent) {
+                self.parents.insert(child.clone(), parent);
+            }
+
+            self.sections.entry(child).or_default().extend(section);
+        }
+    }
+
+    /// Checks that every parent exists and that no section inherits from
+    /// itself, directly or through other sections.
+    pub(crate) fn check_parents(&self) -> Result<(), Error> {
+        for (title, parent) in &self.parents {
+            if !self.sections.contains_key(parent) {
+                return Err(Error::ConfigParse {
+                    section: Some(title.clone()),
+                    key: Some(INHERITS.to_string()),
+                    message: format!("Section '{title}' inherits from missing section '{parent}'"),
+                });
+            }
+
+            let mut chain = vec![title.clone()];
+            let mut current = parent;
+            while let Some(next) = self.parents.get(current) {
+                if let Some(start) = chain.iter().position(|t| t == current) {
+                    let mut cycle = chain[start..].to_vec();
+                    cycle.push(current.clone());
+                    return Err(Error::InheritanceCycle(cycle));
+                }
+                chain.push(current.clone());
+                current = next;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The parents of `title`, nearest first.
+    fn ancestors(&self, title: &str) -> Vec<String> {
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut current = title;
+
+        while let Some(parent) = self.parents.get(current) {
+            if parent == title || ancestors.contains(parent) {
+                break;
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+
+        ancestors
+    }
+}
+
+/// Splits a `[child : parent]` header into the child title and its parent.
+pub(crate) fn split_header(title: &str) -> (&str, Option<&str>) {
+    match title.split_once(':') {
+        Some((child, parent)) => (child.trim(), Some(parent.trim())),
+        None => (title, None),
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{Config, Error};
+use std::collections::HashMap;
+
+type KeyId = (Option<String>, String);
+
+impl Config {
+    /// Returns a copy of the config with every `${...}` reference expanded.
+    ///
+    /// Supported references are:
+    /// * `${key}`: a key in the same section, falling back to the general section
+    /// * `${Section:key}`: a key in another section (`${:key}` for the general section)
+    /// * `${env:NAME}`: an environment variable
+    ///
+    /// Every value of repeated keys is expanded, while references to such a
+    /// key use its last value. Use `$$` to write a literal `$`. Circular
+    /// references are reported as [`Error::InterpolationCycle`] with the
+    /// chain of keys involved.
+    pub fn resolve(&self) -> Result<Config, Error> {
+        let mut resolver = Resolver::new(self);
+        let mut resolved = self.clone();
+
+        for (key, value) in resolved.general_values.iter_mut() {
+            *value = resolver.value(None, key)?;
+        }
+
+        for (title, section) in resolved.sections.iter_mut() {
+            for (key, value) in section.iter_mut() {
+                *value = resolver.value(Some(title), key)?;
+            }
+        }
+
+        for (section, key, _) in self.repeated() {
+            *resolved.multi.entry(section, key) = resolver.values(section, key)?;
+        }
+
+        Ok(resolved)
+    }
+
+    /// Looks up a single value and expands its references, leaving the rest
+    /// of the config untouched.
+    pub fn get_resolved(&self, section: Option<&str>, key: &str) -> Result<Option<String>, Error> {
+        if self.get(section, key).is_none() {
+            return Ok(None);
+        }
+
+        Resolver::new(self).value(section, key).map(Some)
+    }
+}
+
+struct Resolver<'a> {
+    config: &'a Config,
+    stack: Vec<KeyId>,
+    done: HashMap<KeyId, String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            stack: Vec::new(),
+            done: HashMap::new(),
+        }
+    }
+
+    fn value(&mut self, section: Option<&str>, key: &str) -> Result<String, Error> {
+        let id = (section.map(str::to_string), key.to_string());
+        if let Some(value) = self.done.get(&id) {
+            return Ok(value.clone());
+        }
+
+        if let Some(start) = self.stack.iter().position(|entry| *entry == id) {
+            let mut chain: Vec<String> = self.stack[start..].iter().map(display_id).collect();
+            chain.push(display_id(&id));
+            return Err(Error::InterpolationCycle(chain));
+        }
+
+        let raw = self.config.get(section, key).unwrap_or_default();
+        self.stack.push(id.clone());
+        let expanded = self.expand(section, &raw)?;
+        self.stack.pop();
+
+        self.done.insert(id, expanded.clone());
+        Ok(expanded)
+    }
+
+    /// Expands every value of a repeated key.
+    fn values(&mut self, section: Option<&str>, key: &str) -> Result<Vec<String>, Error> {
+        let id = (section.map(str::to_string), key.to_string());
+        self.stack.push(id);
+        let values = self
+            .config
+            .raw_values(section, key)
+            .into_iter()
+            .map(|raw| self.expand(section, raw))
+            .collect();
+        self.stack.pop();
+        values
+    }
+
+    fn expand(&mut self, section: Option<&str>, raw: &str) -> Result<String, Error> {
+        let mut expanded = String::with_capacity(raw.len());
+        let mut rest = raw;
+
+        while let Some(pos) = rest.find('$') {
+            expanded.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            if let Some(after) = rest.strip_prefix('$') {
+                expanded.push('$');
+                rest = after;
+                continue;
+            }
+
+            let Some(after) = rest.strip_prefix('{') else {
+                expanded.push('$');
+                continue;
+            };
+
+            let end = after.find('}').ok_or_else(|| {
+                let (section, key) = self.stack.last().cloned().unwrap_or_default();
+                Error::ConfigParse {
+                    section,
+                    key: Some(key),
+                    message: format!("Unterminated reference in value '{raw}'"),
+                }
+            })?;
+
+            expanded.push_str(&self.lookup(section, &after[..end])?);
+            rest = &after[end + 1..];
+        }
+
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    fn lookup(&mut self, section: Option<&str>, reference: &str) -> Result<String, Error> {
+        let target = match reference.split_once(':') {
+            Some(("env", name)) => {
+                return std::env::var(name).map_err(|_| self.unresolved(reference));
+            }
+            Some(("", key)) => (None, key),
+            Some((title, key)) => (Some(title), key),
+            None if self.config.get(section, reference).is_some() => (section, reference),
+            None => (None, reference),
+        };
+
+        if self.config.get(target.0, target.1).is_none() {
+            return Err(self.unresolved(reference));
+        }
+
+        self.value(target.0, target.1)
+    }
+
+    fn unresolved(&self, reference: &str) -> Error {
+        let (section, key) = self.stack.last().cloned().unwrap_or_default();
+        Error::UnresolvedReference {
+            reference: format!("${{{reference}}}"),
+            section,
+            key,
+        }
+    }
+}
+
+fn display_id((section, key): &KeyId) -> String {
+    match section {
+        Some(section) => format!("{section}:{key}"),
+        None => key.clone(),
+    }
+}
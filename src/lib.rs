@@ -1,11 +1,43 @@
+mod atomic;
 pub mod builder;
+#[cfg(feature = "clap")]
+mod cli;
+mod comment;
 mod config;
+mod configparser;
+mod diff;
+mod document;
 mod error;
+mod fingerprint;
+mod include;
+mod inherit;
+mod interpolate;
+mod lock;
 mod macros;
+mod multi;
+mod options;
+mod order;
 mod outcome;
+mod permissions;
+mod profile;
+mod shared;
+mod strict;
+mod watch;
 
 pub use builder::ConfigBuilder;
+#[cfg(feature = "clap")]
+pub use clap;
+#[cfg(feature = "clap")]
+pub use cli::ClapSection;
 pub use config::{Config, Section};
 pub use config_tools_derive::FromSection;
-pub use error::Error;
-pub use outcome::LoadOutcome;
+pub use configparser::DEFAULT_SECTION;
+pub use diff::{Change, ConfigDiff};
+pub use document::ConfigDocument;
+pub use error::{Error, ParseError};
+pub use options::{LineEnding, ParseOptions, WarningHandler, WriteOptions};
+pub use outcome::{FallbackReason, LoadOutcome};
+pub use permissions::SecretPolicy;
+pub use profile::PROFILE_SEPARATOR;
+pub use shared::SharedConfig;
+pub use watch::{ConfigWatcher, WatchOptions};
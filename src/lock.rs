@@ -0,0 +1,100 @@
+use crate::{Config, Error, ParseOptions, WriteOptions};
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// An advisory lock on a config file, released when dropped.
+///
+/// The lock is taken on a `<path>.lock` file next to the config rather than
+/// on the config itself, because saving replaces the config file and would
+/// leave a lock on the old one behind. Other processes only respect the
+/// lock if they take it too.
+pub(crate) struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    /// Waits until no other process holds an exclusive lock.
+    pub(crate) fn shared(path: &Path) -> Result<Self, Error> {
+        let file = open_lock_file(path)?;
+        file.lock_shared().map_err(|e| Error::load(path, e))?;
+        Ok(Self { file })
+    }
+
+    /// Waits until no other process holds any lock.
+    pub(crate) fn exclusive(path: &Path) -> Result<Self, Error> {
+        let file = open_lock_file(path)?;
+        file.lock().map_err(|e| Error::create(path, e))?;
+        Ok(Self { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// The file locked on behalf of `path`.
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+fn open_lock_file(path: &Path) -> Result<File, Error> {
+    let lock_path = lock_path(path);
+    let opened = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path);
+
+    match opened {
+        // Readers without write access can still lock an existing file.
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            File::open(&lock_path).map_err(|e| Error::load(&lock_path, e))
+        }
+        result => result.map_err(|e| Error::create(&lock_path, e)),
+    }
+}
+
+impl Config {
+    /// Updates a file that other processes may be writing as well.
+    ///
+    /// Takes an exclusive lock, reloads the file (starting from an empty
+    /// config if it doesn't exist), applies `f`, saves the result atomically
+    /// and releases the lock. Returns the saved config.
+    ///
+    /// ```rust,no_run
+    /// # use config_tools::Config;
+    /// let config = Config::modify("counter.ini", |config| {
+    ///     let runs: u32 = config.get_as(None, "runs").unwrap_or(0);
+    ///     config.update(None, "runs", &(runs + 1).to_string());
+    /// })?;
+    /// # Ok::<(), config_tools::Error>(())
+    /// ```
+    pub fn modify<P, F>(path: P, f: F) -> Result<Config, Error>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut Config),
+    {
+        let path = path.as_ref();
+        let _lock = FileLock::exclusive(path)?;
+
+        let mut config = match Self::load_with(path, &ParseOptions::default()) {
+            Ok(config) => config,
+            Err(Error::ConfigLoad { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                Config::default()
+            }
+            Err(e) => return Err(e),
+        };
+
+        f(&mut config);
+        config.save_with(path, &WriteOptions::default())?;
+        Ok(config)
+    }
+}
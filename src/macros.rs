@@ -60,7 +60,10 @@ macro_rules! sectioned_defaults {
 }
 
 #[macro_export]
-/// Generate a `Config` object with default values in a section entitled "DEFAULT".  
+/// Generate a `Config` object with default values in the general section (keys
+/// that don't belong to any section).  
+/// For `configparser`-style fallbacks, use a `"DEFAULT"` section with
+/// [`sectioned_defaults!`] and call [`Config::apply_configparser`](crate::Config::apply_configparser).  
 /// Variables are supported in key and value fields, but must be strings.
 /// # Syntax
 /// ```rust
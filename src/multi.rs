@@ -0,0 +1,129 @@
+use crate::Config;
+use std::collections::BTreeMap;
+
+/// Every value of keys that appear more than once, such as
+/// `server = a` / `server = b`. The last value is also kept in the
+/// regular maps of [`Config`], so lookups with [`Config::get`] see it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub(crate) struct MultiValues {
+    #[serde(default)]
+    pub(crate) general: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub(crate) sections: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl MultiValues {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.general.is_empty() && self.sections.is_empty()
+    }
+
+    pub(crate) fn get(&self, section: Option<&str>, key: &str) -> Option<&Vec<String>> {
+        match section {
+            Some(section) => self.sections.get(section).and_then(|s| s.get(key)),
+            None => self.general.get(key),
+        }
+    }
+
+    pub(crate) fn entry(&mut self, section: Option<&str>, key: &str) -> &mut Vec<String> {
+        let values = match section {
+            Some(section) => self.sections.entry(section.to_string()).or_default(),
+            None => &mut self.general,
+        };
+
+        values.entry(key.to_string()).or_default()
+    }
+
+    pub(crate) fn remove(&mut self, section: Option<&str>, key: &str) {
+        match section {
+            Some(section) => {
+                if let Some(values) = self.sections.get_mut(section) {
+                    values.remove(key);
+                    if values.is_empty() {
+                        self.sections.remove(section);
+                    }
+                }
+            }
+            None => {
+                self.general.remove(key);
+            }
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: MultiValues) {
+        self.general.extend(other.general);
+
+        for (title, keys) in other.sections {
+            self.sections.entry(title).or_default().extend(keys);
+        }
+    }
+}
+
+impl Config {
+    /// Returns every value of a key that is repeated, in the order they were
+    /// read or added. Keys that appear once yield a single value, and
+    /// missing keys an empty list.
+    pub fn get_all(&self, section: Option<&str>, key: &str) -> Vec<String> {
+        let values = self.raw_values(section, key).into_iter();
+        match section {
+            Some(title) => values.map(|v| self.interpolate(title, key, v)).collect(),
+            None => values.map(str::to_string).collect(),
+        }
+    }
+
+    /// The values of a key as they are stored, before any `%(name)s`
+    /// references are expanded.
+    pub(crate) fn raw_values(&self, section: Option<&str>, key: &str) -> Vec<&str> {
+        let section = section.map(|title| self.defining_section(title, key));
+        let Some(value) = self.value(section, key) else {
+            return Vec::new();
+        };
+
+        // Values set directly through the public maps replace the list.
+        match self.multi.get(section, key) {
+            Some(values) if values.last() == Some(value) => {
+                values.iter().map(String::as_str).collect()
+            }
+            _ => vec![value.as_str()],
+        }
+    }
+
+    /// Adds another value to a key, keeping the values it already has.
+    /// [`Config::get`] returns the value added last, and [`Config::save`]
+    /// writes one line per value.
+    pub fn add(&mut self, section: Option<&str>, key: &str, value: &str) -> &mut Self {
+        let current = self.value(section, key).cloned();
+        let values = self.multi.entry(section, key);
+        if values.last() != current.as_ref() {
+            values.clear();
+            values.extend(current);
+        }
+        values.push(value.to_string());
+
+        self.insert(section, key, value);
+        self
+    }
+
+    /// Lists the keys with more than one value, skipping lists that no
+    /// longer match the regular maps.
+    pub(crate) fn repeated(&self) -> Vec<(Option<&str>, &str, Vec<&str>)> {
+        let general = self.multi.general.keys().map(|key| (None, key));
+        let sections = self
+            .multi
+            .sections
+            .iter()
+            .flat_map(|(title, keys)| keys.keys().map(move |key| (Some(title.as_str()), key)));
+
+        general
+            .chain(sections)
+            .map(|(section, key)| (section, key.as_str(), self.raw_values(section, key)))
+            .filter(|(_, _, values)| values.len() > 1)
+            .collect()
+    }
+
+    fn value(&self, section: Option<&str>, key: &str) -> Option<&String> {
+        match section {
+            Some(section) => self.sections.get(section).and_then(|s| s.get(key)),
+            None => self.general_values.get(key),
+        }
+    }
+}
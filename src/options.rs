@@ -0,0 +1,324 @@
+use crate::{
+    permissions::{self, SecretPolicy},
+    Error,
+};
+use ini::{Ini, Properties};
+use std::{borrow::Cow, fmt, sync::Arc};
+
+/// Controls how INI text is read by [`Config::load_with`](crate::Config::load_with).
+///
+/// The defaults match [`Config::load`](crate::Config::load).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// Characters that start a comment line, `;` and `#` by default. They
+    /// replace the defaults, so with `vec!['#']` a line such as
+    /// `;key = value` is read as a key.
+    pub comment_chars: Vec<char>,
+    /// Treats a comment character preceded by whitespace as the start of a
+    /// comment, as in `port = 80 ; http`.
+    pub inline_comments: bool,
+    /// Interprets `\` escape sequences such as `\n`, `\t` and `\\`.
+    pub escape: bool,
+    /// Removes quotes around values, so `name = "My App"` reads as `My App`.
+    pub strip_quotes: bool,
+    /// Rejects files that would otherwise be read with silent surprises:
+    /// duplicate sections or keys, empty keys, invalid characters and lines
+    /// that are neither sections, keys nor comments. Errors report the line
+    /// position as [`Error::Strict`](crate::Error::Strict).
+    pub strict: bool,
+    /// Allows keys before the first section header. Only enforced in strict
+    /// mode.
+    pub allow_general: bool,
+    /// Reads indented lines after a key as more lines of its value, as
+    /// Python's `configparser` does. Lines ending in `\` are always joined
+    /// with the next one.
+    pub multiline: bool,
+    /// Lets a section inherit the keys of another one, named with a
+    /// `[child : parent]` header or an `inherits = parent` key. Lookups such
+    /// as [`Config::get`](crate::Config::get) fall back to the parent, and
+    /// saving keeps the link instead of copying the parent's keys. When
+    /// off, such headers and keys are read like any other.
+    pub inheritance: bool,
+    /// Follows `include = path` and `include_dir = dir` directives in the
+    /// general section, reading the included files with these same options.
+    /// See [`Config::load_with_includes`](crate::Config::load_with_includes);
+    /// configs that included other files can't be saved.
+    pub includes: bool,
+    /// Takes a shared lock on `<path>.lock` while reading, so that the file
+    /// isn't read while another process saves it with [`WriteOptions::lock`].
+    pub lock: bool,
+    /// What to do when a file holding secrets can be read by its group or
+    /// other users. Only checked on Unix.
+    pub secrets: SecretPolicy,
+    /// Keys whose names contain one of these, ignoring case, are secrets.
+    /// Defaults to `password`, `passwd`, `secret`, `token`, `api_key` and
+    /// `private_key`.
+    pub secret_keys: Vec<String>,
+    /// Receives problems that don't stop the file from loading, such as
+    /// [`SecretPolicy::Warn`]. Without a handler they are ignored.
+    pub on_warning: Option<WarningHandler>,
+}
+
+/// A callback for warnings raised while loading, set as
+/// [`ParseOptions::on_warning`].
+///
+/// ```rust
+/// # use config_tools::{ParseOptions, WarningHandler};
+/// let options = ParseOptions {
+///     on_warning: Some(WarningHandler::new(|warning| eprintln!("warning: {warning}"))),
+///     ..ParseOptions::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct WarningHandler(Arc<dyn Fn(&Error) + Send + Sync>);
+
+impl WarningHandler {
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn warn(&self, warning: &Error) {
+        (self.0)(warning)
+    }
+}
+
+impl fmt::Debug for WarningHandler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("WarningHandler(..)")
+    }
+}
+
+/// Handlers are equal when they are clones of each other.
+impl PartialEq for WarningHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for WarningHandler {}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            comment_chars: vec![';', '#'],
+            inline_comments: false,
+            escape: true,
+            strip_quotes: true,
+            strict: false,
+            allow_general: true,
+            multiline: false,
+            inheritance: false,
+            includes: false,
+            lock: false,
+            secrets: SecretPolicy::Ignore,
+            secret_keys: permissions::default_secret_keys(),
+            on_warning: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub(crate) fn to_ini(&self) -> ini::ParseOption {
+        ini::ParseOption {
+            enabled_quote: self.strip_quotes,
+            enabled_escape: self.escape,
+            enabled_indented_mutiline_value: self.multiline,
+            ..ini::ParseOption::default()
+        }
+    }
+
+    /// Whether comments start with exactly `;` and `#`, as the underlying
+    /// parser expects.
+    fn default_comments(&self) -> bool {
+        self.comment_chars.iter().all(|c| matches!(c, ';' | '#'))
+            && self.comment_chars.contains(&';')
+            && self.comment_chars.contains(&'#')
+    }
+
+    /// Rewrites the text so that the underlying parser understands it:
+    /// comment lines become `;` comments, lines starting with `;` or `#`
+    /// that aren't comments get a `\` escape, and inline comments are cut
+    /// off when enabled.
+    pub(crate) fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.default_comments() && !self.inline_comments {
+            return Cow::Borrowed(text);
+        }
+
+        let mut normalized = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            let (content, ending) = split_ending(line);
+            let indent = content.len() - content.trim_start().len();
+            let body = &content[indent..];
+
+            match body.chars().next() {
+                Some(c) if self.comment_chars.contains(&c) => {
+                    normalized.push_str(&content[..indent]);
+                    normalized.push(';');
+                    normalized.push_str(&body[c.len_utf8()..]);
+                }
+                _ => {
+                    let content = if self.inline_comments {
+                        self.strip_inline_comment(content)
+                    } else {
+                        content
+                    };
+                    if body.starts_with([';', '#']) {
+                        normalized.push_str(&content[..indent]);
+                        normalized.push('\\');
+                        normalized.push_str(&content[indent..]);
+                    } else {
+                        normalized.push_str(content);
+                    }
+                }
+            }
+
+            normalized.push_str(ending);
+        }
+
+        Cow::Owned(normalized)
+    }
+
+    /// Drops the `\` that [`ParseOptions::normalize`] puts before keys
+    /// starting with `;` or `#`. The parser already does so when
+    /// [`ParseOptions::escape`] is set.
+    pub(crate) fn unescape_keys(&self, ini: &mut Ini) {
+        if self.escape || self.default_comments() {
+            return;
+        }
+
+        for (_, prop) in ini.iter_mut() {
+            let entries: Vec<(String, String)> = prop
+                .iter()
+                .map(|(key, value)| (unescape_key(key).to_string(), value.to_string()))
+                .collect();
+
+            *prop = Properties::new();
+            for (key, value) in entries {
+                prop.append(key, value);
+            }
+        }
+    }
+
+    /// Cuts a line at the first comment character that follows whitespace
+    /// and isn't inside quotes.
+    fn strip_inline_comment<'a>(&self, line: &'a str) -> &'a str {
+        let mut quote = None;
+        let mut previous = ' ';
+
+        for (i, c) in line.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if self.strip_quotes && matches!(c, '"' | '\'') => quote = Some(c),
+                None if previous.is_whitespace() && i > 0 && self.comment_chars.contains(&c) => {
+                    return line[..i].trim_end();
+                }
+                None => {}
+            }
+            previous = c;
+        }
+
+        line
+    }
+}
+
+/// Line endings written by [`Config::save_with`](crate::Config::save_with).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Controls how INI text is written by [`Config::save_with`](crate::Config::save_with).
+///
+/// The defaults match [`Config::save`](crate::Config::save).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WriteOptions {
+    /// Separates keys from values; usually `=` or `:`.
+    pub separator: char,
+    /// Puts spaces around the separator, as in `key = value`.
+    pub spaced: bool,
+    /// Ends lines with `\n` or `\r\n`.
+    pub line_ending: LineEnding,
+    /// Escapes backslashes and control characters in values, so that they
+    /// can be read back with [`ParseOptions::escape`].
+    pub escape: bool,
+    /// The character that starts comment lines.
+    pub comment_char: char,
+    /// Writes sections and keys in the order they were read from a file or
+    /// added, instead of sorted by name. See [`Config::titles_in_order`].
+    ///
+    /// [`Config::titles_in_order`]: crate::Config::titles_in_order
+    pub preserve_order: bool,
+    /// Writes values containing newlines over several lines, indenting every
+    /// line after the first. Read such files back with
+    /// [`ParseOptions::multiline`]. Leading and trailing newlines and the
+    /// indentation of each line are not preserved.
+    pub multiline: bool,
+    /// Copies the file being replaced to `<path>.bak` before saving.
+    pub backup: bool,
+    /// Takes an exclusive lock on `<path>.lock` while saving. See
+    /// [`Config::modify`](crate::Config::modify) for read-modify-write cycles.
+    pub lock: bool,
+    /// Unix permission bits for the saved file, such as `0o600` for files
+    /// holding credentials. By default a replaced file keeps its permissions
+    /// and a new one follows the process umask. Ignored on other platforms.
+    pub mode: Option<u32>,
+    /// Gives the saved file the owner and group of the file it replaces,
+    /// which usually requires running as root. Unix only.
+    pub preserve_owner: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            separator: '=',
+            spaced: false,
+            line_ending: LineEnding::Lf,
+            escape: true,
+            comment_char: ';',
+            preserve_order: false,
+            multiline: false,
+            backup: false,
+            lock: false,
+            mode: None,
+            preserve_owner: false,
+        }
+    }
+}
+
+impl WriteOptions {
+    pub(crate) fn separator(&self) -> String {
+        if self.spaced {
+            format!(" {} ", self.separator)
+        } else {
+            self.separator.to_string()
+        }
+    }
+}
+
+/// Removes the `\` escaping a key that starts with `;` or `#`.
+pub(crate) fn unescape_key(key: &str) -> &str {
+    match key.strip_prefix('\\') {
+        Some(rest) if rest.starts_with([';', '#']) => rest,
+        _ => key,
+    }
+}
+
+fn split_ending(line: &str) -> (&str, &str) {
+    let content = line.trim_end_matches(['\r', '\n']);
+    (content, &line[content.len()..])
+}
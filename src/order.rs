@@ -0,0 +1,122 @@
+use crate::Config;
+use std::collections::BTreeMap;
+
+/// The order in which the sections and keys of a [`Config`] were read or
+/// added. The maps of [`Config`] are sorted; this records a sequence number
+/// for each name so that the original order can be restored.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Order {
+    next: u64,
+    sections: BTreeMap<String, u64>,
+    keys: BTreeMap<Option<String>, BTreeMap<String, u64>>,
+}
+
+impl Order {
+    pub(crate) fn add_section(&mut self, title: &str) {
+        if !self.sections.contains_key(title) {
+            self.sections.insert(title.to_string(), self.next);
+            self.next += 1;
+        }
+    }
+
+    pub(crate) fn add(&mut self, section: Option<&str>, key: &str) {
+        if let Some(title) = section {
+            self.add_section(title);
+        }
+
+        let keys = self.keys.entry(section.map(str::to_string)).or_default();
+        if !keys.contains_key(key) {
+            keys.insert(key.to_string(), self.next);
+            self.next += 1;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, section: Option<&str>, key: &str) {
+        if let Some(keys) = self.keys.get_mut(&section.map(str::to_string)) {
+            keys.remove(key);
+        }
+    }
+
+    pub(crate) fn remove_section(&mut self, title: &str) {
+        self.sections.remove(title);
+        self.keys.remove(&Some(title.to_string()));
+    }
+
+    /// Moves the position and keys of section `from` to `to`, unless `to`
+    /// already has a position.
+    pub(crate) fn rename_section(&mut self, from: &str, to: &str) {
+        if let Some(position) = self.sections.remove(from) {
+            self.sections.entry(to.to_string()).or_insert(position);
+        }
+        if let Some(keys) = self.keys.remove(&Some(from.to_string())) {
+            let target = self.keys.entry(Some(to.to_string())).or_default();
+            for (key, position) in keys {
+                target.entry(key).or_insert(position);
+            }
+        }
+    }
+
+    /// Appends the sections and keys of `other` that this order doesn't
+    /// know yet, in the order `other` has them.
+    pub(crate) fn merge(&mut self, other: &Order) {
+        for title in sorted(&other.sections, other.sections.keys()) {
+            self.add_section(title);
+        }
+
+        for (section, keys) in &other.keys {
+            for key in sorted(keys, keys.keys()) {
+                self.add(section.as_deref(), key);
+            }
+        }
+    }
+
+    /// Sorts section titles into the order they were added in.
+    pub(crate) fn titles<'a>(&self, titles: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+        sorted(&self.sections, titles)
+    }
+
+    /// Sorts the keys of a section into the order they were added in.
+    pub(crate) fn keys<'a>(
+        &self,
+        section: Option<&str>,
+        keys: impl Iterator<Item = &'a String>,
+    ) -> Vec<&'a str> {
+        match self.keys.get(&section.map(str::to_string)) {
+            Some(positions) => sorted(positions, keys),
+            None => keys.map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Sorts `names` by their position. Names without one, such as those set
+/// directly through the public maps, keep their order after the others.
+fn sorted<'a>(
+    positions: &BTreeMap<String, u64>,
+    names: impl Iterator<Item = &'a String>,
+) -> Vec<&'a str> {
+    let mut names: Vec<&str> = names.map(String::as_str).collect();
+    names.sort_by_key(|name| positions.get(*name).copied().unwrap_or(u64::MAX));
+    names
+}
+
+impl Config {
+    /// Returns the section titles in the order they were read from a file or
+    /// added, unlike [`Config::sections`], which is sorted.
+    pub fn titles_in_order(&self) -> Vec<&str> {
+        self.order.titles(self.sections.keys())
+    }
+
+    /// Returns the keys of a section, or of the general section for `None`,
+    /// in the order they were read from a file or added.
+    pub fn keys_in_order(&self, section: Option<&str>) -> Vec<&str> {
+        let keys = match section {
+            Some(title) => match self.sections.get(title) {
+                Some(keys) => keys,
+                None => return Vec::new(),
+            },
+            None => &self.general_values,
+        };
+
+        self.order.keys(section, keys.keys())
+    }
+}
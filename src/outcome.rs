@@ -1,37 +1,105 @@
-use crate::Config;
+use crate::{Config, Error, ParseError};
+use std::io;
 
 /// The result of loading a configuration, indicating whether the config was
-/// loaded from a file or constructed from a default fallback.
+/// loaded from a file, constructed from a default fallback, or written to a
+/// new file by [`Config::load_or_create`].
 ///
 /// Use [`LoadOutcome::used_default`] to determine which case occurred,
+/// [`LoadOutcome::fallback_reason`] to find out why the file wasn't used,
 /// or extract the inner config using [`LoadOutcome::into_inner`].
 #[derive(Clone, Debug, PartialEq)]
 pub enum LoadOutcome {
-    FromDefault(Config),
+    FromDefault(Config, FallbackReason),
     FromFile(Config),
+    /// The file didn't exist, so the defaults were written to it.
+    Created(Config),
+}
+
+/// Why [`Config::load_or_default_outcome`] fell back to the default config.
+///
+/// A missing file is usually expected on first run, while a malformed one
+/// often means the defaults should not be used silently.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FallbackReason {
+    NotFound,
+    PermissionDenied,
+    /// Any other I/O error while reading the file.
+    Io(io::ErrorKind),
+    /// The file could not be parsed.
+    Malformed(ParseError),
+    /// The file was parsed but its contents were rejected, for example
+    /// because of an inheritance cycle. Holds the error message.
+    Invalid(String),
+}
+
+impl FallbackReason {
+    /// Whether the file exists but could not be used.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, FallbackReason::NotFound)
+    }
+}
+
+impl From<Error> for FallbackReason {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::ConfigLoad { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => FallbackReason::NotFound,
+                io::ErrorKind::PermissionDenied => FallbackReason::PermissionDenied,
+                kind => FallbackReason::Io(kind),
+            },
+            Error::Parse(e) | Error::Strict(e) => FallbackReason::Malformed(e),
+            error => FallbackReason::Invalid(error.to_string()),
+        }
+    }
 }
 
 impl LoadOutcome {
     #[must_use]
     pub fn into_inner(self) -> Config {
         match self {
-            LoadOutcome::FromDefault(cfg) | LoadOutcome::FromFile(cfg) => cfg
+            LoadOutcome::FromDefault(cfg, _)
+            | LoadOutcome::FromFile(cfg)
+            | LoadOutcome::Created(cfg) => cfg,
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn as_mut(&mut self) -> &mut Config {
         match self {
-            LoadOutcome::FromDefault(cfg) | LoadOutcome::FromFile(cfg) => cfg
+            LoadOutcome::FromDefault(cfg, _)
+            | LoadOutcome::FromFile(cfg)
+            | LoadOutcome::Created(cfg) => cfg,
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &Config {
         match self {
-            LoadOutcome::FromDefault(cfg) | LoadOutcome::FromFile(cfg) => cfg
+            LoadOutcome::FromDefault(cfg, _)
+            | LoadOutcome::FromFile(cfg)
+            | LoadOutcome::Created(cfg) => cfg,
         }
     }
 
+    /// Whether the config holds the defaults rather than values read from
+    /// an existing file. This includes newly created files.
     pub fn used_default(&self) -> bool {
-        matches!(self, LoadOutcome::FromDefault(_))
+        matches!(self, LoadOutcome::FromDefault(..) | LoadOutcome::Created(_))
     }
-}
\ No newline at end of file
+
+    /// Whether the defaults were written to a new file.
+    pub fn was_created(&self) -> bool {
+        matches!(self, LoadOutcome::Created(_))
+    }
+
+    /// Returns why the default config was used, or `None` if the file was
+    /// loaded.
+    pub fn fallback_reason(&self) -> Option<&FallbackReason> {
+        match self {
+            LoadOutcome::FromDefault(_, reason) => Some(reason),
+            LoadOutcome::FromFile(_) | LoadOutcome::Created(_) => None,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{Config, Error, ParseOptions, WriteOptions};
+use std::{
+    fs::{File, Metadata},
+    io,
+    path::Path,
+};
+
+/// What [`Config::load_with`] does when a file holding secrets can be read
+/// by other users. See [`ParseOptions::secrets`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SecretPolicy {
+    /// Don't check permissions.
+    #[default]
+    Ignore,
+    /// Pass [`Error::InsecurePermissions`] to [`ParseOptions::on_warning`]
+    /// and load the file anyway.
+    Warn,
+    /// Fail with [`Error::InsecurePermissions`].
+    Deny,
+}
+
+/// Key names treated as secrets unless [`ParseOptions::secret_keys`] says
+/// otherwise.
+pub(crate) fn default_secret_keys() -> Vec<String> {
+    [
+        "password",
+        "passwd",
+        "secret",
+        "token",
+        "api_key",
+        "private_key",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// Gives a freshly created file the permissions and owner it should have:
+/// [`WriteOptions::mode`] if set, otherwise those of the file it replaces.
+/// Called before anything is written to it.
+pub(crate) fn apply_write_options(
+    file: &File,
+    existing: Option<&Metadata>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let mode = options
+            .mode
+            .or_else(|| existing.map(|meta| meta.permissions().mode()));
+        if let Some(mode) = mode {
+            file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        }
+
+        if let Some(meta) = existing.filter(|_| options.preserve_owner) {
+            std::os::unix::fs::fchown(file, Some(meta.uid()), Some(meta.gid()))?;
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = options;
+        if let Some(meta) = existing {
+            file.set_permissions(meta.permissions())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Enforces [`ParseOptions::secrets`] for a config loaded from `path`.
+pub(crate) fn check_secrets(
+    path: &Path,
+    config: &Config,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    if options.secrets == SecretPolicy::Ignore {
+        return Ok(());
+    }
+
+    let Some(mode) = readable_by_others(path) else {
+        return Ok(());
+    };
+
+    let keys = secret_keys(config, &options.secret_keys);
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let error = Error::InsecurePermissions {
+        path: path.to_path_buf(),
+        mode,
+        keys,
+    };
+    match options.secrets {
+        SecretPolicy::Deny => Err(error),
+        _ => {
+            if let Some(handler) = &options.on_warning {
+                handler.warn(&error);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns the file's permission bits if its group or other users can read it.
+#[cfg(unix)]
+fn readable_by_others(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o044 != 0).then_some(mode)
+}
+
+#[cfg(not(unix))]
+fn readable_by_others(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Lists the keys whose names contain one of `patterns`, ignoring case.
+fn secret_keys(config: &Config, patterns: &[String]) -> Vec<String> {
+    let is_secret = |key: &str| {
+        let key = key.to_lowercase();
+        patterns.iter().any(|p| key.contains(&p.to_lowercase()))
+    };
+
+    let general = config.general_values.keys().map(|key| (None, key));
+    let sections = config
+        .sections
+        .iter()
+        .flat_map(|(title, section)| section.keys().map(move |key| (Some(title), key)));
+
+    general
+        .chain(sections)
+        .filter(|(_, key)| is_secret(key))
+        .map(|(section, key)| match section {
+            Some(section) => format!("{section}:{key}"),
+            None => key.clone(),
+        })
+        .collect()
+}
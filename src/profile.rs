@@ -0,0 +1,108 @@
+use crate::{Config, Error, ParseOptions};
+use std::path::Path;
+
+/// Separates a section title from its profile, as in `[Server@prod]`.
+pub const PROFILE_SEPARATOR: char = '@';
+
+impl Config {
+    /// Returns a view of the config for one profile.
+    ///
+    /// Sections titled `Title@profile` are merged over `Title`, key by key,
+    /// or become `Title` if there is no such section, and `[@profile]`
+    /// overrides the general values. Sections for other profiles are kept
+    /// as they are, since only `profile` is known to be a profile name; use
+    /// [`Config::with_profiles`] to leave them out.
+    pub fn with_profile(&self, profile: &str) -> Config {
+        self.with_profiles(profile, &[profile])
+    }
+
+    /// Like [`Config::with_profile`], with `profiles` naming every profile
+    /// the config has. Sections for any of them other than `profile` are
+    /// left out of the result. Titles containing `@` that don't end in one
+    /// of these names, such as `[user@host]`, are kept as they are.
+    pub fn with_profiles(&self, profile: &str, profiles: &[&str]) -> Config {
+        let mut config = Config {
+            general_values: self.general_values.clone(),
+            comments: self.comments.clone(),
+            multi: self.multi.clone(),
+            parents: self.parents.clone(),
+            configparser: self.configparser,
+            order: self.order.clone(),
+            includes: self.includes.clone(),
+            ..Config::default()
+        };
+        let split = |title| split_profile(title, profile, profiles);
+
+        for (title, section) in &self.sections {
+            if split(title).is_none() {
+                config.sections.insert(title.clone(), section.clone());
+            } else {
+                config.multi.sections.remove(title);
+                config.parents.remove(title);
+            }
+        }
+
+        for (title, section) in &self.sections {
+            match split(title) {
+                Some((base, p)) if p == profile => {
+                    let base = (!base.is_empty()).then_some(base);
+                    if let Some(base) = base {
+                        config.sections.entry(base.to_string()).or_default();
+                        config.order.rename_section(title, base);
+                        if let Some(parent) = self.parents.get(title) {
+                            config
+                                .parents
+                                .entry(base.to_string())
+                                .or_insert(parent.clone());
+                        }
+                    }
+                    for key in self.order.keys(Some(title), section.keys()) {
+                        config.update(base, key, &section[key]);
+                        if let Some(values) = self.multi.get(Some(title), key) {
+                            *config.multi.entry(base, key) = values.clone();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        config
+    }
+
+    /// Loads `config.ini` from a directory, merges `config.<profile>.ini` over
+    /// it when that file exists, and applies [`Config::with_profile`].
+    pub fn load_profiled<P: AsRef<Path>>(dir: P, profile: &str) -> Result<Self, Error> {
+        Self::load_profiled_with(dir, profile, &ParseOptions::default())
+    }
+
+    /// [`Config::load_profiled`] reading both files with the given options.
+    pub fn load_profiled_with<P: AsRef<Path>>(
+        dir: P,
+        profile: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let mut config = Self::read_all(&dir.join("config.ini"), options)?;
+
+        let overlay = dir.join(format!("config.{profile}.ini"));
+        if overlay.is_file() {
+            config.merge(Self::read_all(&overlay, options)?);
+        }
+
+        let config = config.with_profile(profile);
+        config.check_parents()?;
+        Ok(config)
+    }
+}
+
+/// Splits a title into its base title and profile, if it ends in
+/// `@profile` or `@` followed by one of `profiles`.
+fn split_profile<'a>(
+    title: &'a str,
+    profile: &str,
+    profiles: &[&str],
+) -> Option<(&'a str, &'a str)> {
+    let (base, name) = title.rsplit_once(PROFILE_SEPARATOR)?;
+    (name == profile || profiles.contains(&name)).then_some((base, name))
+}
@@ -0,0 +1,141 @@
+use crate::Config;
+use arc_swap::ArcSwap;
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// A [`Config`] shared between threads, where readers never block and never
+/// copy the config.
+///
+/// Readers take a [`snapshot`](SharedConfig::snapshot): an `Arc` of the
+/// config as it was at that moment, which later updates don't affect.
+/// Writers build a new version with [`update`](SharedConfig::update) and
+/// swap it in. Each version has a generation number, starting at 0 and
+/// increasing with every update, so readers can cheaply tell whether the
+/// config they hold is still current.
+///
+/// Cloning a `SharedConfig` gives another handle to the same config.
+///
+/// ```
+/// use config_tools::{Config, SharedConfig};
+///
+/// let shared = SharedConfig::new(Config::default());
+/// let reader = shared.clone();
+///
+/// shared.update(|config| {
+///     config.update(Some("Server"), "port", "8080");
+/// });
+///
+/// assert_eq!(reader.snapshot().get(Some("Server"), "port").as_deref(), Some("8080"));
+/// assert_eq!(reader.generation(), 1);
+/// ```
+#[derive(Clone)]
+pub struct SharedConfig {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    current: ArcSwap<Version>,
+    /// Serializes writers, so that concurrent updates don't overwrite each
+    /// other.
+    writer: Mutex<()>,
+}
+
+struct Version {
+    generation: u64,
+    config: Arc<Config>,
+}
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                current: ArcSwap::from_pointee(Version {
+                    generation: 0,
+                    config: Arc::new(config),
+                }),
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// The current config. Holding on to it doesn't block writers.
+    pub fn snapshot(&self) -> Arc<Config> {
+        Arc::clone(&self.inner.current.load().config)
+    }
+
+    /// The current config together with its generation.
+    pub fn snapshot_with_generation(&self) -> (Arc<Config>, u64) {
+        let version = self.inner.current.load();
+        (Arc::clone(&version.config), version.generation)
+    }
+
+    /// The generation of the current config. It increases by one with every
+    /// update.
+    pub fn generation(&self) -> u64 {
+        self.inner.current.load().generation
+    }
+
+    /// Applies `f` to a copy of the current config and makes the result the
+    /// new current config. Readers see either the old config or the new
+    /// one, never a partial update. Returns what `f` returns.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Config) -> R,
+    {
+        let _writer = self
+            .inner
+            .writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let current = self.inner.current.load();
+        let mut config = Config::clone(&current.config);
+        let result = f(&mut config);
+        self.store(current.generation + 1, config);
+        result
+    }
+
+    /// Makes `config` the current config and returns a snapshot of it.
+    pub fn replace(&self, config: Config) -> Arc<Config> {
+        let _writer = self
+            .inner
+            .writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        self.store(self.generation() + 1, config)
+    }
+
+    fn store(&self, generation: u64, config: Config) -> Arc<Config> {
+        let config = Arc::new(config);
+        self.inner.current.store(Arc::new(Version {
+            generation,
+            config: Arc::clone(&config),
+        }));
+        config
+    }
+}
+
+impl Default for SharedConfig {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+impl From<Config> for SharedConfig {
+    fn from(config: Config) -> Self {
+        Self::new(config)
+    }
+}
+
+impl fmt::Debug for SharedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (config, generation) = self.snapshot_with_generation();
+        f.debug_struct("SharedConfig")
+            .field("generation", &generation)
+            .field("config", &config)
+            .finish()
+    }
+}
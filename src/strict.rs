@@ -0,0 +1,123 @@
+use crate::{
+    document::{self, ConfigDocument, Line},
+    inherit, Error, ParseError, ParseOptions,
+};
+use std::collections::HashMap;
+
+impl ConfigDocument {
+    /// Checks the document against the rules of strict mode, reporting the
+    /// first violation with its position in `text`.
+    pub(crate) fn check_strict(&self, text: &str, options: &ParseOptions) -> Result<(), Error> {
+        let mut sections: HashMap<&str, usize> = HashMap::new();
+        let mut keys: HashMap<&str, usize> = HashMap::new();
+        let mut in_section = false;
+        let mut number = 0;
+
+        for line in &self.lines {
+            number += 1;
+            let error = |column: usize, message: String| {
+                Error::Strict(ParseError::new(text, number, column, message))
+            };
+
+            match line {
+                Line::Other(raw) => {
+                    if let Some((column, message)) = document::malformed(raw) {
+                        return Err(error(column, message));
+                    }
+                }
+                Line::Section { raw, title } => {
+                    let title = if options.inheritance {
+                        inherit::split_header(title).0
+                    } else {
+                        title
+                    };
+                    if title.is_empty() {
+                        return Err(error(indent(raw), "Empty section name".to_string()));
+                    }
+
+                    if let Some((i, c)) = title.char_indices().find(|(_, c)| invalid_char(*c)) {
+                        let column = column(raw, raw.find(title).unwrap_or_default() + i);
+                        return Err(error(
+                            column,
+                            format!("Invalid character {c:?} in section name '{title}'"),
+                        ));
+                    }
+
+                    if let Some(first) = sections.insert(title, number) {
+                        return Err(error(
+                            indent(raw),
+                            format!("Duplicate section '{title}' (first defined on line {first})"),
+                        ));
+                    }
+
+                    keys.clear();
+                    in_section = true;
+                }
+                Line::Entry {
+                    prefix,
+                    key,
+                    value,
+                    continuation,
+                    ..
+                } => {
+                    let start = indent(prefix);
+
+                    if key.is_empty() {
+                        return Err(error(start, "Empty key".to_string()));
+                    }
+
+                    if !in_section && !options.allow_general {
+                        return Err(error(
+                            start,
+                            format!("Key '{key}' is outside of any section"),
+                        ));
+                    }
+
+                    let key_start = prefix.len() - prefix.trim_start().len();
+                    if let Some((i, c)) = key
+                        .char_indices()
+                        .find(|(_, c)| invalid_char(*c) || *c == '"')
+                    {
+                        return Err(error(
+                            column(prefix, key_start + i),
+                            format!("Invalid character {c:?} in key '{key}'"),
+                        ));
+                    }
+
+                    if let Some((i, c)) = value.char_indices().find(|(_, c)| c.is_control()) {
+                        let raw = format!("{prefix}{value}");
+                        return Err(error(
+                            column(&raw, prefix.len() + i),
+                            format!("Invalid character {c:?} in value of '{key}'"),
+                        ));
+                    }
+
+                    if let Some(first) = keys.insert(key, number) {
+                        return Err(error(
+                            start,
+                            format!("Duplicate key '{key}' (first defined on line {first})"),
+                        ));
+                    }
+
+                    number += continuation.len();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_char(c: char) -> bool {
+    c.is_control() || matches!(c, '[' | ']')
+}
+
+/// The column of the byte offset `index` in `raw`, counting from 1.
+fn column(raw: &str, index: usize) -> usize {
+    raw[..index].chars().count() + 1
+}
+
+/// The column of the first non-whitespace character.
+fn indent(raw: &str) -> usize {
+    column(raw, raw.len() - raw.trim_start().len())
+}
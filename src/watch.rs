@@ -0,0 +1,343 @@
+use crate::{Config, ConfigDiff, Error, ParseOptions, Section, SharedConfig};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Controls how [`ConfigWatcher::watch_with`] notices and reads changes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WatchOptions {
+    /// How the file is read on every reload.
+    pub parse: ParseOptions,
+    /// How often the file's size and modification time are checked. With
+    /// the `notify` feature, changes are usually noticed right away and
+    /// polling only catches events the platform missed.
+    pub poll_interval: Duration,
+    /// How long the file must stay unchanged before it is reloaded, so that
+    /// an editor writing it in several steps causes a single reload.
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            poll_interval: Duration::from_secs(1),
+            debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+type ChangeCallback = Box<dyn FnMut(&Config, &ConfigDiff) + Send>;
+type ErrorCallback = Box<dyn FnMut(&Error) + Send>;
+type Validator = Box<dyn FnMut(&Config) -> Result<(), Error> + Send>;
+
+/// Reloads a config file whenever it changes, for services that shouldn't
+/// need a restart to pick up new settings.
+///
+/// A background thread checks the file, waits for writes to settle and
+/// parses it again. Callbacks registered with [`ConfigWatcher::on_change`]
+/// receive the new config and what changed. Files that fail to load or are
+/// rejected by a [validator](ConfigWatcher::validate) are reported to
+/// [`ConfigWatcher::on_error`] and leave the last good config in place. The
+/// thread stops when the watcher is dropped.
+///
+/// ```no_run
+/// use config_tools::ConfigWatcher;
+///
+/// let watcher = ConfigWatcher::watch("config.ini")?;
+/// watcher.on_change(|config, diff| {
+///     if diff.contains(Some("Server"), "port") {
+///         println!("Now listening on {:?}", config.get(Some("Server"), "port"));
+///     }
+/// });
+/// # Ok::<(), config_tools::Error>(())
+/// ```
+pub struct ConfigWatcher {
+    shared: Arc<Shared>,
+    wake: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+    #[cfg(feature = "notify")]
+    _native: Option<notify::RecommendedWatcher>,
+}
+
+struct Shared {
+    path: PathBuf,
+    options: WatchOptions,
+    current: SharedConfig,
+    /// Held while a reload runs, so that reloads never overlap.
+    reloading: Mutex<()>,
+    validators: Mutex<Vec<Validator>>,
+    on_change: Mutex<Vec<ChangeCallback>>,
+    on_error: Mutex<Vec<ErrorCallback>>,
+    stop: AtomicBool,
+}
+
+impl ConfigWatcher {
+    /// Loads `path` and starts watching it with the default [`WatchOptions`].
+    pub fn watch<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::watch_with(path, WatchOptions::default())
+    }
+
+    /// Loads `path` and starts watching it. Fails if the file can't be
+    /// loaded in the first place.
+    pub fn watch_with<P: AsRef<Path>>(path: P, options: WatchOptions) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let seen = stamp(&path);
+        let config = Config::load_with(&path, &options.parse)?;
+
+        let shared = Arc::new(Shared {
+            path,
+            options,
+            current: SharedConfig::new(config),
+            reloading: Mutex::new(()),
+            validators: Mutex::new(Vec::new()),
+            on_change: Mutex::new(Vec::new()),
+            on_error: Mutex::new(Vec::new()),
+            stop: AtomicBool::new(false),
+        });
+
+        let (wake, events) = mpsc::channel();
+        #[cfg(feature = "notify")]
+        let native = native_watcher(&shared.path, wake.clone());
+
+        let worker = Arc::clone(&shared);
+        let thread = thread::Builder::new()
+            .name("config-watcher".to_string())
+            .spawn(move || worker.run(&events, seen))
+            .map_err(|e| Error::load(&shared.path, e))?;
+
+        Ok(Self {
+            shared,
+            wake,
+            thread: Some(thread),
+            #[cfg(feature = "notify")]
+            _native: native,
+        })
+    }
+
+    /// The most recently loaded config. The returned snapshot doesn't
+    /// change when the file is reloaded; call this again for the new one.
+    pub fn config(&self) -> Arc<Config> {
+        self.shared.current.snapshot()
+    }
+
+    /// A handle to the config that follows every reload, for passing to
+    /// other threads. Its generation increases with every successful reload.
+    ///
+    /// Changes made through [`SharedConfig::update`] last until the next
+    /// reload, which replaces them with the file's contents.
+    pub fn shared(&self) -> SharedConfig {
+        self.shared.current.clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+
+    /// Reloads the file right away, as the watcher's thread does when the
+    /// file changes, and returns what changed. On error the current config
+    /// is kept and the error is returned rather than passed to
+    /// [`ConfigWatcher::on_error`].
+    ///
+    /// Change callbacks run on the calling thread, so this must not be
+    /// called from one of them.
+    pub fn reload(&self) -> Result<ConfigDiff, Error> {
+        self.shared.reload()
+    }
+
+    /// Checks every reloaded config with `validator` before it replaces the
+    /// current one. A config that fails any validator is discarded, and the
+    /// error is reported like a file that failed to parse.
+    ///
+    /// The current config is checked right away; if it fails, the error is
+    /// returned and the validator isn't added.
+    pub fn validate<F>(&self, mut validator: F) -> Result<&Self, Error>
+    where
+        F: FnMut(&Config) -> Result<(), Error> + Send + 'static,
+    {
+        let _reloading = lock(&self.shared.reloading);
+        validator(&self.shared.current.snapshot())?;
+        lock(&self.shared.validators).push(Box::new(validator));
+        Ok(self)
+    }
+
+    /// Requires the section `title` to parse as `T`, typically a
+    /// [`FromSection`](crate::FromSection) struct. The section is read with
+    /// [`Config::section`], so inherited and `[DEFAULT]` keys count. A
+    /// missing section is parsed as an empty one, so it fails if `T` has
+    /// required fields. See [`ConfigWatcher::validate`].
+    pub fn require_section<T: Section>(&self, title: &str) -> Result<&Self, Error> {
+        let title = title.to_string();
+        self.validate(move |config| {
+            let section = config.section(&title).unwrap_or_default();
+            match T::from_section(&section) {
+                Ok(_) => Ok(()),
+                Err(Error::ConfigParse {
+                    section: None,
+                    key,
+                    message,
+                }) => Err(Error::ConfigParse {
+                    section: Some(title.clone()),
+                    key,
+                    message,
+                }),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    /// Calls `callback` with the new config and its differences from the
+    /// previous one after every reload that changed a value. Callbacks run
+    /// on the watcher's thread, in the order they were registered.
+    pub fn on_change<F>(&self, callback: F) -> &Self
+    where
+        F: FnMut(&Config, &ConfigDiff) + Send + 'static,
+    {
+        lock(&self.shared.on_change).push(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` when the changed file can't be loaded or is
+    /// rejected by a validator. The previous config stays current until the
+    /// file is fixed.
+    pub fn on_error<F>(&self, callback: F) -> &Self
+    where
+        F: FnMut(&Error) + Send + 'static,
+    {
+        lock(&self.shared.on_error).push(Box::new(callback));
+        self
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        let _ = self.wake.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Shared {
+    /// Waits for the file to change, then for the writes to settle, and
+    /// reloads it. `seen` identifies the version that is currently loaded.
+    fn run(&self, events: &Receiver<()>, mut seen: Option<Stamp>) {
+        loop {
+            if let Err(RecvTimeoutError::Disconnected) =
+                events.recv_timeout(self.options.poll_interval)
+            {
+                return;
+            }
+            if self.stopped() {
+                return;
+            }
+
+            let mut current = stamp(&self.path);
+            if current == seen {
+                continue;
+            }
+
+            loop {
+                if !self.sleep(events, self.options.debounce) {
+                    return;
+                }
+                let settled = stamp(&self.path);
+                if settled == current {
+                    break;
+                }
+                current = settled;
+            }
+
+            seen = current;
+            if let Err(e) = self.reload() {
+                for callback in lock(&self.on_error).iter_mut() {
+                    callback(&e);
+                }
+            }
+        }
+    }
+
+    /// Loads and validates the file, then swaps it in and notifies the
+    /// change callbacks. Nothing changes if any step fails.
+    fn reload(&self) -> Result<ConfigDiff, Error> {
+        let _reloading = lock(&self.reloading);
+
+        let config = Config::load_with(&self.path, &self.options.parse)?;
+        for validator in lock(&self.validators).iter_mut() {
+            validator(&config)?;
+        }
+
+        let diff = self.current.snapshot().diff(&config);
+        let config = self.current.replace(config);
+
+        if !diff.is_empty() {
+            for callback in lock(&self.on_change).iter_mut() {
+                callback(&config, &diff);
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Waits for `duration`, ignoring file events. Returns `false` if the
+    /// watcher is being dropped.
+    fn sleep(&self, events: &Receiver<()>, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match events.recv_timeout(left) {
+                Ok(()) if !self.stopped() => continue,
+                Err(RecvTimeoutError::Timeout) => return !self.stopped(),
+                _ => return false,
+            }
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+}
+
+/// The size and modification time of a file, or `None` if it is missing.
+type Stamp = (u64, Option<SystemTime>);
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()))
+}
+
+/// Locks `mutex`, even if a callback panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Wakes the watcher's thread whenever something in the file's directory
+/// changes. The directory is watched rather than the file because saving
+/// atomically replaces the file. Returns `None` if the platform can't watch
+/// it, leaving polling to notice changes.
+#[cfg(feature = "notify")]
+fn native_watcher(path: &Path, wake: Sender<()>) -> Option<notify::RecommendedWatcher> {
+    use notify::Watcher;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut watcher = notify::recommended_watcher(move |_: notify::Result<notify::Event>| {
+        let _ = wake.send(());
+    })
+    .ok()?;
+    watcher
+        .watch(dir, notify::RecursiveMode::NonRecursive)
+        .ok()?;
+    Some(watcher)
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "clap")]
+use config_tools::{clap::Command, sectioned_defaults, ClapSection, FromSection};
+
+#[derive(Debug, FromSection, PartialEq)]
+#[section(env_prefix = "CLAP_TESTS_")]
+struct ServerSettings {
+    /// Address to bind to.
+    address: String,
+    port: u16,
+    #[section(env = "CLAP_TESTS_WORKERS")]
+    worker_threads: u16,
+}
+
+fn command() -> Command {
+    ServerSettings::augment_command(Command::new("test"))
+}
+
+#[test]
+fn test_clap_args_from_fields() {
+    let args = ServerSettings::args();
+    let longs: Vec<_> = args.iter().map(|a| a.get_long().unwrap()).collect();
+
+    assert_eq!(longs, ["address", "port", "worker-threads"]);
+    assert_eq!(
+        args[0].get_help().map(|h| h.to_string()),
+        Some("Address to bind to.".to_string())
+    );
+    assert_eq!(
+        args[2].get_env().and_then(|e| e.to_str()),
+        Some("CLAP_TESTS_WORKERS")
+    );
+}
+
+#[test]
+fn test_clap_precedence() {
+    let defaults = sectioned_defaults! {
+        ["Server"] {
+            "address" => "127.0.0.1",
+            "port" => "8080",
+            "worker_threads" => "4",
+        }
+    };
+    let file = sectioned_defaults! {
+        ["Server"] {
+            "port" => "9000",
+            "worker_threads" => "8",
+        }
+    };
+
+    std::env::set_var("CLAP_TESTS_WORKERS", "16");
+    std::env::set_var("CLAP_TESTS_PORT", "9500");
+
+    let matches = command().get_matches_from(["test", "--port", "10000"]);
+    std::env::remove_var("CLAP_TESTS_WORKERS");
+    std::env::remove_var("CLAP_TESTS_PORT");
+
+    let settings = ServerSettings::from_sources(
+        &matches,
+        file.section("Server").as_deref(),
+        defaults.section("Server").as_deref(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        settings,
+        ServerSettings {
+            address: "127.0.0.1".to_string(), // defaults
+            port: 10000,                      // CLI beats env and file
+            worker_threads: 16,               // env beats file
+        }
+    );
+}
+
+#[test]
+fn test_clap_invalid_value() {
+    let matches = command().get_matches_from(["test", "--address", "::1", "--port", "http"]);
+    let result = ServerSettings::from_sources(&matches, None, None);
+
+    assert!(result.is_err(), "Unparseable CLI values should be rejected");
+}
+
+#[test]
+fn test_clap_missing_value() {
+    let matches = command().get_matches_from(["test", "--port", "8080"]);
+    let result = ServerSettings::from_sources(&matches, None, None);
+
+    assert!(
+        result.is_err(),
+        "Fields missing from every source should be rejected"
+    );
+}
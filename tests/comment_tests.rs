@@ -0,0 +1,104 @@
+use config_tools::Config;
+use std::fs;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_builder_comments_are_saved() {
+    let config = Config::builder()
+        .comment("Enables verbose output")
+        .set("debug", "true")
+        .comment("Web server settings\nRestart required")
+        .section("Server")
+        .comment("Port to listen on")
+        .set("port", "8080")
+        .set("host", "localhost")
+        .build();
+
+    let tmp = NamedTempFile::new().unwrap();
+    config.save(tmp.path()).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "; Enables verbose output\ndebug=true\n\n\
+         ; Web server settings\n; Restart required\n[Server]\n\
+         host=localhost\n; Port to listen on\nport=8080\n"
+    );
+}
+
+#[test]
+fn test_comments_are_loaded() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "# File header\n\n; Log level\nlevel = info\n\n\
+         ; Database\n[db]\n# Connection string\n#   (primary only)\nurl = postgres://db\nuser = app\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+
+    assert_eq!(config.comment(None, "level"), Some("Log level"));
+    assert_eq!(config.section_comment("db"), Some("Database"));
+    assert_eq!(
+        config.comment(Some("db"), "url"),
+        Some("Connection string\n  (primary only)")
+    );
+    assert_eq!(config.comment(Some("db"), "user"), None);
+
+    let out = NamedTempFile::new().unwrap();
+    config.save(out.path()).unwrap();
+    let reloaded = Config::load(out.path()).unwrap();
+    assert_eq!(
+        reloaded.comment(Some("db"), "url"),
+        config.comment(Some("db"), "url")
+    );
+}
+
+#[test]
+fn test_set_comment() {
+    let mut config = Config::builder()
+        .section("App")
+        .set("theme", "dark")
+        .build();
+    config
+        .set_comment(Some("App"), "theme", "light or dark")
+        .set_section_comment("App", "Appearance");
+
+    assert_eq!(config.comment(Some("App"), "theme"), Some("light or dark"));
+    assert_eq!(config.section_comment("App"), Some("Appearance"));
+    assert_eq!(
+        config,
+        Config::builder()
+            .section("App")
+            .set("theme", "dark")
+            .build()
+    );
+}
+
+#[test]
+fn test_remove_drops_comments() {
+    let mut config = Config::builder()
+        .comment("Appearance")
+        .section("App")
+        .comment("light or dark")
+        .set("theme", "dark")
+        .comment("A monospace font")
+        .set("font", "mono")
+        .build();
+
+    config.remove(Some("App"), "theme");
+    config.update(Some("App"), "theme", "light");
+    assert_eq!(config.comment(Some("App"), "theme"), None);
+
+    config.remove_section("App");
+    config.update(Some("App"), "font", "sans");
+    assert_eq!(config.section_comment("App"), None);
+    assert_eq!(config.comment(Some("App"), "font"), None);
+
+    let tmp = NamedTempFile::new().unwrap();
+    config.save(tmp.path()).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "[App]\nfont=sans\n"
+    );
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant, clippy::bool_assert_comparison)]
+
 use config_tools::Config;
 use tempfile::NamedTempFile;
 
@@ -0,0 +1,173 @@
+use config_tools::{sectioned_defaults, Config, Error};
+use std::fs;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_configparser_default_fallbacks() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "[DEFAULT]\nhost = localhost\nport = 80\n\n\
+         [web]\nport = 8080\nurl = http://%(host)s:%(port)s/\n\n\
+         [api]\nratio = 50%%\n",
+    )
+    .unwrap();
+
+    let config = Config::load_configparser(tmp.path()).unwrap();
+
+    assert_eq!(
+        config.get(Some("web"), "host"),
+        Some("localhost".to_string())
+    );
+    assert_eq!(
+        config.get(Some("web"), "url"),
+        Some("http://localhost:8080/".to_string())
+    );
+    assert_eq!(config.get(Some("api"), "port"), Some("80".to_string()));
+    assert_eq!(config.get(Some("api"), "ratio"), Some("50%".to_string()));
+}
+
+#[test]
+fn test_configparser_is_opt_in() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "[DEFAULT]\nhost = localhost\n[web]\nurl = %(host)s\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.get(Some("web"), "host"), None);
+    assert_eq!(config.get(Some("web"), "url"), Some("%(host)s".to_string()));
+}
+
+#[test]
+fn test_configparser_interpolation_errors() {
+    let mut cycle = sectioned_defaults! {
+        ["web"] {
+            "a" => "%(b)s",
+            "b" => "%(a)s",
+        }
+    };
+    assert!(matches!(
+        cycle.apply_configparser(),
+        Err(Error::InterpolationCycle(_))
+    ));
+
+    let mut missing = sectioned_defaults! {
+        ["web"] {
+            "a" => "%(nope)s",
+        }
+    };
+    assert!(matches!(
+        missing.apply_configparser(),
+        Err(Error::UnresolvedReference { .. })
+    ));
+}
+
+#[test]
+fn test_configparser_keeps_defaults_in_default_section() {
+    let mut config = sectioned_defaults! {
+        ["DEFAULT"] {
+            "host" => "localhost",
+            "url" => "http://%(host)s/",
+        }
+        ["web"] {
+            "port" => "8080",
+        }
+        ["api"] {
+            "host" => "api.local",
+        }
+    };
+    config.apply_configparser().unwrap();
+
+    assert_eq!(
+        config.get(Some("web"), "url"),
+        Some("http://localhost/".to_string())
+    );
+    assert_eq!(
+        config.get(Some("api"), "url"),
+        Some("http://api.local/".to_string())
+    );
+    assert_eq!(config.sections()["web"].len(), 1);
+    assert_eq!(config.section("web").unwrap().len(), 3);
+    assert_eq!(
+        config.section("api").unwrap()["url"],
+        "http://api.local/".to_string()
+    );
+
+    let tmp = NamedTempFile::new().unwrap();
+    config.save(tmp.path()).unwrap();
+    let saved = fs::read_to_string(tmp.path()).unwrap();
+    assert!(saved.contains("[web]\nport=8080\n"));
+}
+
+#[test]
+fn test_configparser_round_trip() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "[DEFAULT]\nhost = localhost\n\n[App]\nratio = 100%%\nurl = http://%(host)s/\n",
+    )
+    .unwrap();
+
+    let mut config = Config::load_configparser(tmp.path()).unwrap();
+    assert_eq!(config.get(Some("App"), "ratio"), Some("100%".to_string()));
+
+    let app = config.section("App").unwrap();
+    assert_eq!(app["host"], "localhost");
+    assert_eq!(app["ratio"], "100%");
+    assert_eq!(app["url"], "http://localhost/");
+
+    // Values are stored as written, so saving keeps the escapes and
+    // references instead of their expansion.
+    config.update(Some("App"), "host", "app.local");
+    config.save(tmp.path()).unwrap();
+    let saved = fs::read_to_string(tmp.path()).unwrap();
+    assert!(
+        saved.contains("[App]\nhost=app.local\nratio=100%%\nurl=http://%(host)s/\n"),
+        "{saved}"
+    );
+
+    let reloaded = Config::load_configparser(tmp.path()).unwrap();
+    assert_eq!(reloaded, config);
+    assert_eq!(
+        reloaded.get(Some("App"), "url"),
+        Some("http://app.local/".to_string())
+    );
+}
+
+#[test]
+fn test_configparser_error_leaves_config_unchanged() {
+    let mut config = sectioned_defaults! {
+        ["DEFAULT"] {
+            "host" => "localhost",
+        }
+        ["a"] {
+            "url" => "%(host)s",
+        }
+        ["b"] {
+            "broken" => "%(nope)s",
+        }
+    };
+    let before = config.clone();
+
+    assert!(config.apply_configparser().is_err());
+    assert_eq!(config, before);
+    assert_eq!(config.get(Some("a"), "url"), Some("%(host)s".to_string()));
+}
+
+#[test]
+fn test_configparser_names_are_case_sensitive() {
+    let mut config = sectioned_defaults! {
+        ["web"] {
+            "Host" => "localhost",
+            "url" => "%(host)s",
+        }
+    };
+
+    assert!(matches!(
+        config.apply_configparser(),
+        Err(Error::UnresolvedReference { .. })
+    ));
+}
@@ -0,0 +1,72 @@
+use config_tools::{Config, Error, WriteOptions};
+use std::fs;
+
+fn defaults() -> Config {
+    Config::builder()
+        .comment("Generated on first run")
+        .section("Server")
+        .set("port", "8080")
+        .build()
+}
+
+#[test]
+fn test_load_or_create_writes_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested/app/config.ini");
+
+    let outcome = Config::load_or_create(&path, defaults()).unwrap();
+    assert!(outcome.was_created());
+    assert!(outcome.used_default());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "; Generated on first run\n[Server]\nport=8080\n"
+    );
+
+    // The second run reads the file it wrote.
+    fs::write(&path, "[Server]\nport = 9090\n").unwrap();
+    let outcome = Config::load_or_create(&path, defaults()).unwrap();
+    assert!(!outcome.used_default());
+    assert_eq!(
+        outcome.as_ref().get(Some("Server"), "port"),
+        Some("9090".to_string())
+    );
+}
+
+#[test]
+fn test_load_or_create_keeps_malformed_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    fs::write(&path, "[Server\nport = 9090\n").unwrap();
+
+    let result = Config::load_or_create(&path, defaults());
+    assert!(matches!(result, Err(Error::Parse(_))));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[Server\nport = 9090\n");
+}
+
+#[test]
+fn test_load_or_create_with_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    let options = WriteOptions {
+        spaced: true,
+        mode: Some(0o600),
+        ..WriteOptions::default()
+    };
+
+    let outcome = Config::load_or_create_with(&path, defaults(), &options).unwrap();
+    assert!(outcome.was_created());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "; Generated on first run\n[Server]\nport = 8080\n"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Only the config file is left behind.
+    let names: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+    assert_eq!(names.len(), 1);
+}
@@ -1,4 +1,6 @@
-use config_tools::{sectioned_defaults, Config, FromSection, Section};
+#![allow(clippy::needless_borrow)]
+
+use config_tools::{sectioned_defaults, Config, Error, FromSection, Section};
 
 #[derive(Debug, FromSection, PartialEq)]
 struct ServerSettings {
@@ -50,3 +52,32 @@ fn test_section_parsing_into_struct() {
     };
     assert_eq!(server_settings, expected_settings);
 }
+
+#[test]
+fn test_missing_and_invalid_values_are_reported_separately() {
+    let config = sectioned_defaults! {
+        ["Missing"] {
+            "address" => "192.168.1.1",
+            "threads" => "8",
+        }
+        ["Invalid"] {
+            "address" => "192.168.1.1",
+            "port" => "http",
+            "threads" => "8",
+        }
+    };
+
+    let missing = ServerSettings::from_section(&config.section("Missing").unwrap()).unwrap_err();
+    assert!(matches!(
+        missing,
+        Error::ConfigParse { key: Some(ref key), ref message, .. }
+            if key == "port" && message == "Missing value"
+    ));
+
+    let invalid = ServerSettings::from_section(&config.section("Invalid").unwrap()).unwrap_err();
+    assert!(matches!(
+        invalid,
+        Error::ConfigParse { key: Some(ref key), ref message, .. }
+            if key == "port" && message == "Invalid value 'http'"
+    ));
+}
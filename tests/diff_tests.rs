@@ -0,0 +1,51 @@
+use config_tools::{Change, Config};
+
+#[test]
+fn test_diff() {
+    let old: Config = "debug = true\n[Server]\nhost = localhost\nport = 80\n"
+        .parse()
+        .unwrap();
+    let new: Config = "[Server]\nhost = localhost\nport = 8080\n[Cache]\nsize = 10\n"
+        .parse()
+        .unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.changes.len(), 3);
+    assert_eq!(
+        diff.get(Some("Server"), "port"),
+        Some(&Change {
+            section: Some("Server".to_string()),
+            key: "port".to_string(),
+            old: Some("80".to_string()),
+            new: Some("8080".to_string()),
+            old_values: vec!["80".to_string()],
+            new_values: vec!["8080".to_string()],
+        })
+    );
+    assert!(diff.get(None, "debug").unwrap().is_removed());
+    assert!(diff.get(Some("Cache"), "size").unwrap().is_added());
+    assert!(!diff.contains(Some("Server"), "host"));
+    assert!(diff.touches_section("Cache"));
+}
+
+#[test]
+fn test_diff_of_equal_configs_is_empty() {
+    let config: Config = "[Server]\nport = 80\n".parse().unwrap();
+    assert!(config.diff(&config.clone()).is_empty());
+    assert_eq!(config.diff(&Config::default()).changes.len(), 1);
+}
+
+#[test]
+fn test_diff_compares_every_value_of_repeated_keys() {
+    let old: Config = "[Upstream]\nserver = a\nserver = b\n".parse().unwrap();
+    let new: Config = "[Upstream]\nserver = c\nserver = b\n".parse().unwrap();
+
+    let diff = old.diff(&new);
+    let change = diff.get(Some("Upstream"), "server").unwrap();
+    assert_eq!(change.old, change.new);
+    assert_eq!(change.old_values, ["a", "b"]);
+    assert_eq!(change.new_values, ["c", "b"]);
+    assert!(!change.is_added() && !change.is_removed());
+
+    assert!(old.diff(&old.clone()).is_empty());
+}
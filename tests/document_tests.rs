@@ -0,0 +1,100 @@
+use config_tools::{Config, ConfigDocument};
+use std::fs;
+use tempfile::NamedTempFile;
+
+const HAND_WRITTEN: &str = "\
+; Maintained by the ops team
+name = demo
+
+# Web server
+[Server]
+port = 8080   
+host=localhost
+
+[Database]
+; primary only
+url = postgres://db
+";
+
+#[test]
+fn test_document_roundtrip_is_lossless() {
+    let doc: ConfigDocument = HAND_WRITTEN.parse().unwrap();
+    assert_eq!(doc.to_string(), HAND_WRITTEN);
+
+    let crlf = HAND_WRITTEN.replace('\n', "\r\n");
+    assert_eq!(ConfigDocument::parse(&crlf).to_string(), crlf);
+}
+
+#[test]
+fn test_document_update_touches_only_one_line() {
+    let mut doc = ConfigDocument::parse(HAND_WRITTEN);
+    doc.update(Some("Server"), "host", "0.0.0.0");
+
+    let expected = HAND_WRITTEN.replace("host=localhost", "host=0.0.0.0");
+    assert_eq!(doc.to_string(), expected);
+}
+
+#[test]
+fn test_document_insert_and_remove() {
+    let mut doc = ConfigDocument::parse(HAND_WRITTEN);
+    doc.update(Some("Server"), "threads", "4")
+        .update(None, "debug", "true")
+        .update(Some("Cache"), "size", "64")
+        .remove(Some("Database"), "url");
+
+    assert_eq!(
+        doc.to_string(),
+        "\
+; Maintained by the ops team
+name = demo
+debug = true
+
+# Web server
+[Server]
+port = 8080   
+host=localhost
+threads = 4
+
+[Database]
+; primary only
+
+[Cache]
+size = 64
+"
+    );
+
+    doc.remove_section("Database");
+    assert!(!doc.to_string().contains("primary only"));
+}
+
+#[test]
+fn test_document_apply_config() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), HAND_WRITTEN).unwrap();
+
+    let mut doc = ConfigDocument::load(tmp.path()).unwrap();
+    let mut config = doc.to_config().unwrap();
+    config.update(Some("Server"), "port", "9090");
+    config.remove_section("Database");
+
+    doc.apply(&config).unwrap().save(tmp.path()).unwrap();
+
+    let saved = fs::read_to_string(tmp.path()).unwrap();
+    assert!(saved.starts_with("; Maintained by the ops team\nname = demo\n"));
+    assert!(saved.contains("# Web server\n[Server]\nport = 9090   \nhost=localhost\n"));
+    assert!(!saved.contains("[Database]"));
+    assert_eq!(Config::load(tmp.path()).unwrap(), config);
+}
+
+#[test]
+fn test_document_from_config() {
+    let config = Config::builder()
+        .set("debug", "true")
+        .section("App")
+        .set("path", "C:\\app")
+        .build();
+
+    let doc = ConfigDocument::from_config(&config);
+    assert_eq!(doc.to_string(), "debug=true\n\n[App]\npath=C:\\\\app\n");
+    assert_eq!(doc.to_config().unwrap(), config);
+}
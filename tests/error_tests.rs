@@ -0,0 +1,84 @@
+use config_tools::{Config, Error, ParseError, ParseOptions};
+use std::fs;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_parse_error_location() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), "name = app\n\n[Server\nport = 80\n").unwrap();
+
+    let Err(Error::Parse(e)) = Config::load(tmp.path()) else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(e.path.as_deref(), Some(tmp.path()));
+    assert_eq!((e.line, e.column), (3, 8));
+    assert_eq!(e.message, "Expected ']' to close the section header");
+    assert_eq!(e.line_text, "[Server");
+    assert!(e
+        .to_string()
+        .starts_with(&format!("{}:3:", tmp.path().display())));
+}
+
+#[test]
+fn test_parse_error_render() {
+    let error = ParseError {
+        path: Some("config.ini".into()),
+        line: 12,
+        column: 3,
+        line_text: "\tport = 80".to_string(),
+        message: "Duplicate key 'port'".to_string(),
+    };
+
+    assert_eq!(
+        error.render(),
+        "error: Duplicate key 'port'\n  --> config.ini:12:3\n   |\n12 | \tport = 80\n   | \t ^\n"
+    );
+}
+
+#[test]
+fn test_strict_error_location() {
+    let options = ParseOptions {
+        strict: true,
+        multiline: true,
+        ..ParseOptions::default()
+    };
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), "[A]\nquery = a\n    b\nke]y = 1\n").unwrap();
+
+    let Err(Error::Strict(e)) = Config::load_with(tmp.path(), &options) else {
+        panic!("expected a strict error");
+    };
+    assert_eq!((e.line, e.column), (4, 3));
+    assert_eq!(e.line_text, "ke]y = 1");
+
+    let error = Error::Strict(e.clone());
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.downcast_ref::<ParseError>(), Some(&e));
+}
+
+#[test]
+fn test_error_source_and_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.ini");
+
+    let error = Config::load(&missing).unwrap_err();
+    assert!(matches!(&error, Error::ConfigLoad { path: Some(p), .. } if *p == missing));
+
+    let source = std::error::Error::source(&error).unwrap();
+    let io = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::NotFound);
+
+    let boxed: Box<dyn std::error::Error> = Box::new(error);
+    assert!(boxed.to_string().contains("missing.ini"));
+
+    let config: Config = "[Server]\nurl = http://${host}\n".parse().unwrap();
+    let error = config.resolve().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unresolved reference ${host} in 'Server:url'"
+    );
+    assert!(matches!(
+        error,
+        Error::UnresolvedReference { section: Some(s), key, .. } if s == "Server" && key == "url"
+    ));
+}
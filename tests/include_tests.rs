@@ -0,0 +1,150 @@
+use config_tools::{Config, Error, ParseOptions};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_include_relative_paths() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("extra")).unwrap();
+    fs::write(
+        dir.path().join("main.ini"),
+        "include = extra/db.ini\nname = main\n[Server]\nport = 8080\nhost = localhost\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("extra/db.ini"),
+        "[Server]\nport = 9090\n[Database]\nhost = db\n",
+    )
+    .unwrap();
+
+    let config = Config::load_with_includes(dir.path().join("main.ini")).unwrap();
+
+    assert_eq!(config.get(None, "name"), Some("main".to_string()));
+    assert_eq!(
+        config.get(None, "include"),
+        None,
+        "Directives should be removed"
+    );
+    assert_eq!(config.get(Some("Server"), "port"), Some("9090".to_string()));
+    assert_eq!(
+        config.get(Some("Server"), "host"),
+        Some("localhost".to_string())
+    );
+    assert_eq!(config.get(Some("Database"), "host"), Some("db".to_string()));
+}
+
+#[test]
+fn test_include_dir_lexical_order() {
+    let dir = TempDir::new().unwrap();
+    let conf_d = dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(
+        dir.path().join("config.ini"),
+        "include_dir = conf.d\n[App]\nlevel = base\n",
+    )
+    .unwrap();
+    fs::write(conf_d.join("20-late.ini"), "[App]\nlevel = late\n").unwrap();
+    fs::write(
+        conf_d.join("10-early.ini"),
+        "[App]\nlevel = early\ncolor = red\n",
+    )
+    .unwrap();
+    fs::write(conf_d.join("README"), "not a config").unwrap();
+
+    let config = Config::load_with_includes(dir.path().join("config.ini")).unwrap();
+    assert_eq!(config.get(Some("App"), "level"), Some("late".to_string()));
+    assert_eq!(config.get(Some("App"), "color"), Some("red".to_string()));
+
+    let drop_ins = Config::load_dir(&conf_d).unwrap();
+    assert_eq!(drop_ins.get(Some("App"), "level"), Some("late".to_string()));
+    assert_eq!(
+        drop_ins.included_files(),
+        [conf_d.join("10-early.ini"), conf_d.join("20-late.ini")]
+    );
+}
+
+#[test]
+fn test_include_cycle() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.ini"), "include = b.ini\n").unwrap();
+    fs::write(dir.path().join("b.ini"), "include = a.ini\n").unwrap();
+
+    match Config::load_with_includes(dir.path().join("a.ini")) {
+        Err(Error::IncludeCycle(chain)) => assert_eq!(chain.len(), 3),
+        other => panic!("Expected an include cycle, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_include_missing_file() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.ini"), "include = missing.ini\n").unwrap();
+
+    assert!(Config::load_with_includes(dir.path().join("main.ini")).is_err());
+}
+
+#[test]
+fn test_load_with_includes_option() {
+    let dir = TempDir::new().unwrap();
+    let main = dir.path().join("main.ini");
+    fs::write(&main, "include = extra.ini\n[Server]\nport = 80\n").unwrap();
+    fs::write(
+        dir.path().join("extra.ini"),
+        "[Server]\nport = 9090 ; from extra\n",
+    )
+    .unwrap();
+
+    // Includes are only followed when asked for.
+    let plain = Config::load(&main).unwrap();
+    assert_eq!(plain.get(None, "include"), Some("extra.ini".to_string()));
+    assert_eq!(plain.get(Some("Server"), "port"), Some("80".to_string()));
+
+    // Included files are read with the same options.
+    let options = ParseOptions {
+        includes: true,
+        inline_comments: true,
+        ..ParseOptions::default()
+    };
+    let mut config = Config::load_with(&main, &options).unwrap();
+    assert_eq!(config.get(Some("Server"), "port"), Some("9090".to_string()));
+
+    // Saving would copy the included values into the main file.
+    config.update(Some("Server"), "port", "81");
+    assert_eq!(config.included_files(), [dir.path().join("extra.ini")]);
+    for result in [
+        config.save(&main).map(|_| ()),
+        config.save_checked(&main).map(|_| ()),
+        config.save_merged(&main).map(|_| ()),
+    ] {
+        match result {
+            Err(Error::HasIncludes { path, includes }) => {
+                assert_eq!(path, main);
+                assert_eq!(includes, [dir.path().join("extra.ini")]);
+            }
+            other => panic!("Expected the save to be refused, got {other:?}"),
+        }
+    }
+    assert_eq!(
+        fs::read_to_string(&main).unwrap(),
+        "include = extra.ini\n[Server]\nport = 80\n"
+    );
+
+    // Without directives, the file can be saved as usual.
+    fs::write(&main, "[Server]\nport = 80\n").unwrap();
+    let mut config = Config::load_with(&main, &options).unwrap();
+    assert!(config.included_files().is_empty());
+    config.save_checked(&main).unwrap();
+}
+
+#[test]
+fn test_include_dir_missing() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.ini"), "include_dir = missing\n").unwrap();
+
+    match Config::load_with_includes(dir.path().join("main.ini")) {
+        Err(Error::ConfigLoad { path, .. }) => {
+            assert_eq!(path, Some(dir.path().join("missing")));
+        }
+        other => panic!("Expected a load error, got {other:?}"),
+    }
+}
@@ -0,0 +1,173 @@
+use config_tools::{Config, Error, FromSection, ParseOptions, Section};
+use std::fs;
+use tempfile::NamedTempFile;
+
+const DATABASES: &str = "[db.primary]\nhost = primary\nport = 5432\nuser = app\n\n\
+                         [db.replica : db.primary]\nhost = replica\n\n\
+                         [db.reporting]\ninherits = db.replica\nuser = reports\n";
+
+fn inheritance() -> ParseOptions {
+    ParseOptions {
+        inheritance: true,
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn test_inheritance_header_and_key() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), DATABASES).unwrap();
+
+    let config = Config::load_with(tmp.path(), &inheritance()).unwrap();
+
+    // Sections keep their own keys; lookups fall back to the parent.
+    let replica = &config.sections()["db.replica"];
+    assert_eq!(replica.get("host"), Some(&"replica".to_string()));
+    assert_eq!(replica.get("port"), None);
+    assert_eq!(
+        config.get(Some("db.replica"), "port"),
+        Some("5432".to_string())
+    );
+
+    // Multi-level chain: db.reporting -> db.replica -> db.primary
+    assert_eq!(
+        config.get(Some("db.reporting"), "host"),
+        Some("replica".to_string())
+    );
+    assert_eq!(
+        config.get_as::<u16>(Some("db.reporting"), "port"),
+        Some(5432)
+    );
+    assert_eq!(
+        config.get(Some("db.reporting"), "user"),
+        Some("reports".to_string())
+    );
+    assert_eq!(config.get(Some("db.reporting"), "inherits"), None);
+    assert!(config.section("db.replica : db.primary").is_none());
+
+    let reporting = config.section("db.reporting").unwrap();
+    assert_eq!(reporting.get("host"), Some(&"replica".to_string()));
+    assert_eq!(reporting.get("port"), Some(&"5432".to_string()));
+    assert_eq!(reporting.get("user"), Some(&"reports".to_string()));
+}
+
+#[test]
+fn test_inheritance_is_kept_when_saving() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), DATABASES).unwrap();
+
+    let mut config = Config::load_with(tmp.path(), &inheritance()).unwrap();
+    config.update(Some("db.primary"), "port", "6543");
+    config.save(tmp.path()).unwrap();
+
+    let text = fs::read_to_string(tmp.path()).unwrap();
+    assert!(
+        text.contains("[db.replica : db.primary]\nhost=replica\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("[db.reporting : db.replica]\nuser=reports\n"),
+        "{text}"
+    );
+
+    let reloaded = Config::load_with(tmp.path(), &inheritance()).unwrap();
+    assert_eq!(reloaded, config);
+    assert_eq!(
+        reloaded.get(Some("db.reporting"), "port"),
+        Some("6543".to_string())
+    );
+}
+
+#[derive(Debug, FromSection, PartialEq)]
+struct Database {
+    host: String,
+    port: u16,
+    user: String,
+}
+
+#[test]
+fn test_inherited_section_from_section() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), DATABASES).unwrap();
+
+    let mut config = Config::load_with(tmp.path(), &inheritance()).unwrap();
+    config.update(Some("db.primary"), "port", "6543");
+
+    let reporting = Database::from_section(&config.section("db.reporting").unwrap()).unwrap();
+    assert_eq!(
+        reporting,
+        Database {
+            host: "replica".to_string(),
+            port: 6543,
+            user: "reports".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_inheritance_is_opt_in() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "[server:main]\nx = 1\n[base]\ninherits = other\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.get(Some("server:main"), "x"), Some("1".to_string()));
+    assert_eq!(
+        config.get(Some("base"), "inherits"),
+        Some("other".to_string())
+    );
+
+    config.save(tmp.path()).unwrap();
+    assert_eq!(Config::load(tmp.path()).unwrap(), config);
+
+    match Config::load_with(tmp.path(), &inheritance()) {
+        Err(Error::ConfigParse { message, .. }) => {
+            assert!(
+                message.contains("inherits from missing section"),
+                "{message}"
+            );
+        }
+        other => panic!("Expected a missing parent, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_inheritance_builder() {
+    let mut config = Config::builder()
+        .section("base")
+        .set("timeout", "30")
+        .section("child")
+        .set("inherits", "base")
+        .build();
+
+    config.resolve_inheritance().unwrap();
+    assert_eq!(config.get(Some("child"), "timeout"), Some("30".to_string()));
+}
+
+#[test]
+fn test_inheritance_cycle() {
+    let mut config = Config::builder()
+        .section("a")
+        .set("inherits", "b")
+        .section("b")
+        .set("inherits", "a")
+        .build();
+
+    match config.resolve_inheritance() {
+        Err(Error::InheritanceCycle(chain)) => assert_eq!(chain, ["a", "b", "a"]),
+        other => panic!("Expected an inheritance cycle, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_inheritance_missing_parent() {
+    let mut config = Config::builder()
+        .section("child")
+        .set("inherits", "missing")
+        .build();
+
+    assert!(config.resolve_inheritance().is_err());
+}
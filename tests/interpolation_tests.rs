@@ -0,0 +1,97 @@
+use config_tools::{sectioned_defaults, Error};
+
+#[test]
+fn test_interpolation_references() {
+    std::env::set_var("INTERPOLATION_TESTS_USER", "admin");
+
+    let config = sectioned_defaults! {
+        {
+            "base" => "/srv/app",
+        }
+        ["Paths"] {
+            "data" => "${base}/data",
+            "cache" => "${data}/cache",
+        }
+        ["Server"] {
+            "log" => "${Paths:data}/server.log",
+            "user" => "${env:INTERPOLATION_TESTS_USER}",
+            "price" => "$$5",
+        }
+    };
+
+    let resolved = config.resolve().unwrap();
+    std::env::remove_var("INTERPOLATION_TESTS_USER");
+
+    assert_eq!(
+        resolved.get(Some("Paths"), "cache"),
+        Some("/srv/app/data/cache".to_string())
+    );
+    assert_eq!(
+        resolved.get(Some("Server"), "log"),
+        Some("/srv/app/data/server.log".to_string())
+    );
+    assert_eq!(
+        resolved.get(Some("Server"), "user"),
+        Some("admin".to_string())
+    );
+    assert_eq!(
+        resolved.get(Some("Server"), "price"),
+        Some("$5".to_string())
+    );
+
+    // The original config keeps its raw values.
+    assert_eq!(
+        config.get(Some("Paths"), "data"),
+        Some("${base}/data".to_string())
+    );
+}
+
+#[test]
+fn test_interpolation_get_resolved() {
+    let config = sectioned_defaults! {
+        ["App"] {
+            "name" => "demo",
+            "title" => "${name} v1",
+        }
+    };
+
+    assert_eq!(
+        config.get_resolved(Some("App"), "title").unwrap(),
+        Some("demo v1".to_string())
+    );
+    assert_eq!(config.get_resolved(Some("App"), "missing").unwrap(), None);
+}
+
+#[test]
+fn test_interpolation_cycle() {
+    let config = sectioned_defaults! {
+        ["A"] {
+            "one" => "${two}",
+            "two" => "${B:three}",
+        }
+        ["B"] {
+            "three" => "${A:one}",
+        }
+    };
+
+    match config.get_resolved(Some("A"), "one") {
+        Err(Error::InterpolationCycle(chain)) => {
+            assert_eq!(chain, ["A:one", "A:two", "B:three", "A:one"]);
+        }
+        other => panic!("Expected a cycle error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_interpolation_unresolved() {
+    let config = sectioned_defaults! {
+        ["App"] {
+            "path" => "${missing}/bin",
+        }
+    };
+
+    assert!(matches!(
+        config.resolve(),
+        Err(Error::UnresolvedReference { .. })
+    ));
+}
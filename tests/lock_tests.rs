@@ -0,0 +1,51 @@
+use config_tools::{Config, ParseOptions, WriteOptions};
+use std::{fs, thread};
+
+#[test]
+fn test_modify_serializes_writers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("counter.ini");
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let path = path.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    Config::modify(&path, |config| {
+                        let runs: u32 = config.get_as(Some("Stats"), "runs").unwrap_or(0);
+                        config.update(Some("Stats"), "runs", &(runs + 1).to_string());
+                    })
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.get_as::<u32>(Some("Stats"), "runs"), Some(40));
+}
+
+#[test]
+fn test_locked_load_and_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+
+    let write = WriteOptions {
+        lock: true,
+        ..WriteOptions::default()
+    };
+    let read = ParseOptions {
+        lock: true,
+        ..ParseOptions::default()
+    };
+
+    let config = Config::builder().set("name", "app").build();
+    config.save_with(&path, &write).unwrap();
+    assert_eq!(Config::load_with(&path, &read).unwrap(), config);
+    assert!(dir.path().join("config.ini.lock").exists());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "name=app\n");
+}
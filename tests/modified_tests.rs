@@ -0,0 +1,138 @@
+use config_tools::{Config, Error, WriteOptions};
+use std::fs;
+
+fn write(path: &std::path::Path, text: &str) {
+    fs::write(path, text).unwrap();
+}
+
+#[test]
+fn test_save_checked_detects_external_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    write(&path, "[Server]\nport=80\n");
+
+    let mut config = Config::load(&path).unwrap();
+    config.update(Some("Server"), "port", "8080");
+    config.save_checked(&path).unwrap();
+
+    // Saving again compares against the file just written.
+    config.update(Some("Server"), "host", "localhost");
+    config.save_checked(&path).unwrap();
+
+    write(&path, "[Server]\nport=9090\n; edited by hand\n");
+    config.update(Some("Server"), "port", "1");
+    let error = config.save_checked(&path).unwrap_err();
+    assert!(matches!(&error, Error::Modified { conflicts, .. } if conflicts.is_empty()));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[Server]\nport=9090\n; edited by hand\n"
+    );
+
+    // A loaded config is still equal to one built by hand.
+    let loaded = Config::load(&path).unwrap();
+    let built = Config::builder()
+        .section("Server")
+        .set("port", "9090")
+        .build();
+    assert_eq!(loaded, built);
+}
+
+#[test]
+fn test_save_merged_combines_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    write(
+        &path,
+        "[Server]\nhost=localhost\nport=80\n[Old]\nkey=value\n",
+    );
+
+    let mut config = Config::load(&path).unwrap();
+    config.update(Some("Server"), "port", "8080");
+
+    write(&path, "[Server]\nhost=example.com\nport=80\nworkers=4\n");
+    config.save_merged(&path).unwrap();
+
+    let saved = Config::load(&path).unwrap();
+    let expected = Config::builder()
+        .section("Server")
+        .set("host", "example.com")
+        .set("port", "8080")
+        .set("workers", "4")
+        .build();
+    assert_eq!(saved, expected);
+}
+
+#[test]
+fn test_save_merged_reports_conflicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    write(&path, "[Server]\nport=80\n");
+
+    let mut config = Config::load(&path).unwrap();
+    config.update(Some("Server"), "port", "8080");
+    write(&path, "[Server]\nport=9090\n");
+
+    let Err(Error::Modified { conflicts, .. }) = config.save_merged(&path) else {
+        panic!("expected a conflict");
+    };
+    assert_eq!(conflicts, vec!["Server:port"]);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[Server]\nport=9090\n");
+}
+
+#[test]
+fn test_save_merged_keeps_comments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    write(
+        &path,
+        "[Server]\n; Public name\nhost=localhost\n; Listen port\nport=80\n",
+    );
+
+    let mut config = Config::load(&path).unwrap();
+    config.update(Some("Server"), "port", "8080");
+
+    write(
+        &path,
+        "[Server]\n; Public name\nhost=example.com\n; Port to listen on\nport=80\n; Added by hand\nworkers=4\n",
+    );
+    config.save_merged(&path).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[Server]\n; Public name\nhost=example.com\n; Port to listen on\nport=8080\n; Added by hand\nworkers=4\n"
+    );
+}
+
+#[test]
+fn test_save_checked_and_merged_with_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    write(&path, "[Server]\nport = 80\n");
+
+    let options = WriteOptions {
+        spaced: true,
+        lock: true,
+        ..WriteOptions::default()
+    };
+
+    let mut config = Config::load(&path).unwrap();
+    config.update(Some("Server"), "port", "8080");
+    config.save_checked_with(&path, &options).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[Server]\nport = 8080\n"
+    );
+
+    write(&path, "[Server]\nport = 8080\nhost = example.com\n");
+    config.update(Some("Server"), "workers", "4");
+    assert!(matches!(
+        config.save_checked_with(&path, &options),
+        Err(Error::Modified { .. })
+    ));
+
+    config.save_merged_with(&path, &options).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[Server]\nhost = example.com\nport = 8080\nworkers = 4\n"
+    );
+}
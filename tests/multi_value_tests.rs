@@ -0,0 +1,139 @@
+use config_tools::{Config, ConfigDocument, ParseOptions};
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_get_all_preserves_repeated_keys() {
+    let config: Config = "mirror = a\n[Upstream]\nserver = a\nserver = b\nport = 80\n"
+        .parse()
+        .unwrap();
+
+    assert_eq!(config.get_all(Some("Upstream"), "server"), vec!["a", "b"]);
+    assert_eq!(
+        config.get(Some("Upstream"), "server"),
+        Some("b".to_string())
+    );
+    assert_eq!(config.get_all(Some("Upstream"), "port"), vec!["80"]);
+    assert_eq!(config.get_all(None, "mirror"), vec!["a"]);
+    assert!(config.get_all(None, "missing").is_empty());
+}
+
+#[test]
+fn test_add_round_trips_through_save() {
+    let tmp = NamedTempFile::new().unwrap();
+    let config = Config::builder()
+        .section("Upstream")
+        .comment("Tried in order")
+        .add("server", "a")
+        .add("server", "b")
+        .set("port", "80")
+        .build();
+
+    config.save(tmp.path()).unwrap();
+    let text = std::fs::read_to_string(tmp.path()).unwrap();
+    assert!(text.contains("; Tried in order\nserver=a\nserver=b\n"));
+
+    let loaded = Config::load(tmp.path()).unwrap();
+    assert_eq!(loaded, config);
+    assert_eq!(loaded.get_all(Some("Upstream"), "server"), vec!["a", "b"]);
+}
+
+#[test]
+fn test_update_and_merge_replace_all_values() {
+    let mut config: Config = "server = a\nserver = b\n".parse().unwrap();
+    config.add(None, "server", "c");
+    assert_eq!(config.get_all(None, "server"), vec!["a", "b", "c"]);
+
+    config.update(None, "server", "d");
+    assert_eq!(config.get_all(None, "server"), vec!["d"]);
+
+    let mut base: Config = "server = a\nserver = b\n".parse().unwrap();
+    base.merge(Config::builder().set("server", "e").build());
+    assert_eq!(base.get_all(None, "server"), vec!["e"]);
+
+    // Writing to the public maps directly replaces the list as well.
+    let mut direct: Config = "server = a\nserver = b\n".parse().unwrap();
+    direct
+        .general_values
+        .insert("server".to_string(), "f".to_string());
+    assert_eq!(direct.get_all(None, "server"), vec!["f"]);
+}
+
+#[test]
+fn test_document_apply_multi_values() {
+    let mut doc: ConfigDocument = "; servers\nserver = a\nport = 80\n".parse().unwrap();
+
+    let mut config = doc.to_config().unwrap();
+    config.add(None, "server", "b");
+    doc.apply(&config).unwrap();
+
+    assert_eq!(
+        doc.to_string(),
+        "; servers\nserver = a\nserver = b\nport = 80\n"
+    );
+    assert_eq!(doc.to_config().unwrap(), config);
+
+    config.update(None, "server", "c");
+    doc.apply(&config).unwrap();
+    assert_eq!(doc.to_string(), "; servers\nserver = c\nport = 80\n");
+}
+
+#[test]
+fn test_resolve_expands_every_value() {
+    let config: Config = "domain = example.com\n[Upstream]\nserver = a.${domain}\nserver = b.${domain}\nprimary = ${server}\n"
+        .parse()
+        .unwrap();
+
+    let resolved = config.resolve().unwrap();
+    assert_eq!(
+        resolved.get_all(Some("Upstream"), "server"),
+        vec!["a.example.com", "b.example.com"]
+    );
+    assert_eq!(
+        resolved.get(Some("Upstream"), "primary"),
+        Some("b.example.com".to_string())
+    );
+}
+
+#[test]
+fn test_profile_override_replaces_every_value() {
+    let config: Config = "[Upstream]\nserver = a\nserver = b\n[Upstream@prod]\nserver = b\n[Upstream@staging]\nserver = c\nserver = d\n"
+        .parse()
+        .unwrap();
+
+    let prod = config.with_profile("prod");
+    assert_eq!(prod.get_all(Some("Upstream"), "server"), vec!["b"]);
+
+    let staging = config.with_profile("staging");
+    assert_eq!(staging.get_all(Some("Upstream"), "server"), vec!["c", "d"]);
+
+    let dev = config.with_profile("dev");
+    assert_eq!(dev.get_all(Some("Upstream"), "server"), vec!["a", "b"]);
+}
+
+#[test]
+fn test_inherited_keys_keep_every_value() {
+    let options = ParseOptions {
+        inheritance: true,
+        ..ParseOptions::default()
+    };
+    let tmp = NamedTempFile::new().unwrap();
+    std::fs::write(
+        tmp.path(),
+        "[base]\nserver = a\nserver = b\n[child : base]\nport = 80\n",
+    )
+    .unwrap();
+    let config = Config::load_with(tmp.path(), &options).unwrap();
+    assert_eq!(config.get_all(Some("child"), "server"), vec!["a", "b"]);
+
+    config.save(tmp.path()).unwrap();
+    let loaded = Config::load_with(tmp.path(), &options).unwrap();
+    assert_eq!(loaded.get_all(Some("child"), "server"), vec!["a", "b"]);
+
+    let mut resolved = config.clone();
+    resolved.resolve_inheritance().unwrap();
+    assert_eq!(resolved.get_all(Some("child"), "server"), vec!["a", "b"]);
+    assert_eq!(
+        resolved.resolve().unwrap().get_all(Some("child"), "server"),
+        vec!["a", "b"]
+    );
+}
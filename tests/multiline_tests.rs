@@ -0,0 +1,93 @@
+use config_tools::{Config, ConfigDocument, ParseOptions, WriteOptions};
+use std::fs;
+use tempfile::NamedTempFile;
+
+fn multiline() -> ParseOptions {
+    ParseOptions {
+        multiline: true,
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn test_indented_continuation_lines() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "; Report query\n[Report]\nquery = SELECT *\n    FROM users\n\n    WHERE active = 1\nlimit = 10\n",
+    )
+    .unwrap();
+
+    let config = Config::load_with(tmp.path(), &multiline()).unwrap();
+    assert_eq!(
+        config.get(Some("Report"), "query"),
+        Some("SELECT *\nFROM users\n\nWHERE active = 1".to_string())
+    );
+    assert_eq!(config.get(Some("Report"), "limit"), Some("10".to_string()));
+    assert_eq!(config.section_comment("Report"), Some("Report query"));
+
+    let strict = ParseOptions {
+        strict: true,
+        ..multiline()
+    };
+    assert!(Config::load_with(tmp.path(), &strict).is_ok());
+}
+
+#[test]
+fn test_backslash_continuation() {
+    let config: Config = "motd = Welcome to \\\n  the server\nport = 80\n"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        config.get(None, "motd"),
+        Some("Welcome to   the server".to_string())
+    );
+    assert_eq!(config.get(None, "port"), Some("80".to_string()));
+}
+
+#[test]
+fn test_multiline_values_round_trip() {
+    let tmp = NamedTempFile::new().unwrap();
+    let mut config = Config::default();
+    config.update(
+        Some("Tls"),
+        "chain",
+        "-----BEGIN-----\nAAAA\n\nBBBB\n-----END-----",
+    );
+    config.update(Some("Tls"), "port", "443");
+
+    // By default newlines are escaped and the value stays on one line.
+    config.save(tmp.path()).unwrap();
+    assert!(fs::read_to_string(tmp.path())
+        .unwrap()
+        .contains("chain=-----BEGIN-----\\nAAAA"));
+    assert_eq!(Config::load(tmp.path()).unwrap(), config);
+
+    let options = WriteOptions {
+        multiline: true,
+        ..WriteOptions::default()
+    };
+    config.save_with(tmp.path(), &options).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "[Tls]\nchain=-----BEGIN-----\n    AAAA\n\n    BBBB\n    -----END-----\nport=443\n"
+    );
+    assert_eq!(Config::load_with(tmp.path(), &multiline()).unwrap(), config);
+}
+
+#[test]
+fn test_document_keeps_continuation_lines() {
+    let text = "[Report]\nquery = SELECT *\n    FROM users\nlimit = 10\n";
+    let mut doc = ConfigDocument::parse_with(text, &multiline());
+    assert_eq!(doc.to_string(), text);
+
+    doc.update(Some("Report"), "limit", "20");
+    assert_eq!(
+        doc.to_string(),
+        "[Report]\nquery = SELECT *\n    FROM users\nlimit = 20\n"
+    );
+
+    doc.update(Some("Report"), "query", "SELECT 1");
+    assert_eq!(doc.to_string(), "[Report]\nquery = SELECT 1\nlimit = 20\n");
+}
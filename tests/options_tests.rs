@@ -0,0 +1,106 @@
+use config_tools::{Config, LineEnding, ParseOptions, WriteOptions};
+use std::fs;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_parse_options_comments() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "// Generated file\n[Server] ; main server\nhost = \"example.com\" ; public name\nport = 80 // http\nmotd = a;b\n",
+    )
+    .unwrap();
+
+    let options = ParseOptions {
+        comment_chars: vec![';', '#', '/'],
+        inline_comments: true,
+        ..ParseOptions::default()
+    };
+    let config = Config::load_with(tmp.path(), &options).unwrap();
+
+    assert_eq!(
+        config.get(Some("Server"), "host"),
+        Some("example.com".to_string())
+    );
+    assert_eq!(config.get(Some("Server"), "port"), Some("80".to_string()));
+    assert_eq!(config.get(Some("Server"), "motd"), Some("a;b".to_string()));
+
+    // By default the inline comment is part of the value.
+    let literal: Config = "port = 80 ; http\n".parse().unwrap();
+    assert_eq!(literal.get(None, "port"), Some("80 ; http".to_string()));
+}
+
+#[test]
+fn test_comment_chars_replace_defaults() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "% Generated file\n[Channels]\n#general = open\n;random = muted\nlog = on % verbose\n",
+    )
+    .unwrap();
+
+    for escape in [true, false] {
+        let options = ParseOptions {
+            comment_chars: vec!['%'],
+            inline_comments: true,
+            escape,
+            ..ParseOptions::default()
+        };
+        let config = Config::load_with(tmp.path(), &options).unwrap();
+
+        assert_eq!(
+            config.get(Some("Channels"), "#general"),
+            Some("open".to_string())
+        );
+        assert_eq!(
+            config.get(Some("Channels"), ";random"),
+            Some("muted".to_string())
+        );
+        assert_eq!(config.get(Some("Channels"), "log"), Some("on".to_string()));
+        assert_eq!(config.section("Channels").unwrap().len(), 3);
+    }
+}
+
+#[test]
+fn test_parse_options_escape_and_quotes() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), "path = C:\\Windows\nname = \"My App\"\n").unwrap();
+
+    let config = Config::load(tmp.path()).unwrap();
+    assert_eq!(config.get(None, "path"), Some("C:Windows".to_string()));
+    assert_eq!(config.get(None, "name"), Some("My App".to_string()));
+
+    let options = ParseOptions {
+        escape: false,
+        strip_quotes: false,
+        ..ParseOptions::default()
+    };
+    let config = Config::load_with(tmp.path(), &options).unwrap();
+    assert_eq!(config.get(None, "path"), Some("C:\\Windows".to_string()));
+    assert_eq!(config.get(None, "name"), Some("\"My App\"".to_string()));
+}
+
+#[test]
+fn test_write_options() {
+    let config = Config::builder()
+        .section("Server")
+        .comment("Where to listen")
+        .set("host", "localhost")
+        .build();
+
+    let tmp = NamedTempFile::new().unwrap();
+    let options = WriteOptions {
+        separator: ':',
+        spaced: true,
+        line_ending: LineEnding::CrLf,
+        comment_char: '#',
+        ..WriteOptions::default()
+    };
+    config.save_with(tmp.path(), &options).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "[Server]\r\n# Where to listen\r\nhost : localhost\r\n"
+    );
+    assert_eq!(Config::load(tmp.path()).unwrap(), config);
+}
@@ -0,0 +1,84 @@
+use config_tools::{sectioned_defaults, Config, ParseOptions, WriteOptions};
+use std::fs;
+use tempfile::NamedTempFile;
+
+fn ordered() -> WriteOptions {
+    WriteOptions {
+        preserve_order: true,
+        ..WriteOptions::default()
+    }
+}
+
+#[test]
+fn test_builder_order_is_preserved() {
+    let config = Config::builder()
+        .set("zeta", "1")
+        .set("alpha", "2")
+        .section("Zoo")
+        .set("b", "1")
+        .set("a", "2")
+        .section("Aquarium")
+        .set("fish", "3")
+        .build();
+
+    assert_eq!(config.titles_in_order(), ["Zoo", "Aquarium"]);
+    assert_eq!(config.keys_in_order(None), ["zeta", "alpha"]);
+
+    let tmp = NamedTempFile::new().unwrap();
+    config.save_with(tmp.path(), &ordered()).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "zeta=1\nalpha=2\n\n[Zoo]\nb=1\na=2\n\n[Aquarium]\nfish=3\n"
+    );
+
+    // Without the option, files are still sorted.
+    config.save(tmp.path()).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path()).unwrap(),
+        "alpha=2\nzeta=1\n\n[Aquarium]\nfish=3\n\n[Zoo]\na=2\nb=1\n"
+    );
+}
+
+#[test]
+fn test_file_order_is_preserved() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(
+        tmp.path(),
+        "[Window]\nwidth = 720\nheight = 480\n[Base]\nx = 1\n[Child : Base]\nz = 2\ny = 3\n",
+    )
+    .unwrap();
+
+    let options = ParseOptions {
+        inheritance: true,
+        ..ParseOptions::default()
+    };
+    let mut config = Config::load_with(tmp.path(), &options).unwrap();
+    assert_eq!(config.titles_in_order(), ["Window", "Base", "Child"]);
+    assert_eq!(config.keys_in_order(Some("Window")), ["width", "height"]);
+    assert_eq!(config.keys_in_order(Some("Child")), ["z", "y"]);
+
+    config.remove(Some("Window"), "width");
+    config.update(Some("Window"), "depth", "10");
+    assert_eq!(config.keys_in_order(Some("Window")), ["height", "depth"]);
+    assert!(config.keys_in_order(Some("Missing")).is_empty());
+}
+
+#[test]
+fn test_keys_set_through_maps_come_last() {
+    let mut config = sectioned_defaults! {
+        ["Server"] {
+            "port" => "8080",
+        }
+    };
+    config.update(Some("Server"), "host", "localhost");
+    config
+        .sections
+        .get_mut("Server")
+        .unwrap()
+        .insert("debug".to_string(), "true".to_string());
+
+    assert_eq!(
+        config.keys_in_order(Some("Server")),
+        ["port", "host", "debug"]
+    );
+}
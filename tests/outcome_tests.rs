@@ -1,4 +1,6 @@
-use config_tools::{Config, sectioned_defaults};
+use config_tools::{Config, FallbackReason, sectioned_defaults};
+use std::fs;
+use tempfile::NamedTempFile;
 
 #[test]
 fn test_load_outcome_used_default() {
@@ -23,4 +25,25 @@ fn test_load_outcome_mutation() {
 
     outcome.as_mut().update(None, "key", "value");
     assert_eq!(outcome.as_ref().get(None, "key"), Some("value".to_string()));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_load_outcome_fallback_reason() {
+    let outcome = Config::load_or_default_outcome("nonexistent_file.ini", Config::default());
+    assert_eq!(outcome.fallback_reason(), Some(&FallbackReason::NotFound));
+    assert!(!FallbackReason::NotFound.is_error());
+
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), "[Server]\nport = 80\n").unwrap();
+    let outcome = Config::load_or_default_outcome(tmp.path(), Config::default());
+    assert!(!outcome.used_default());
+    assert_eq!(outcome.fallback_reason(), None);
+
+    fs::write(tmp.path(), "[Server]\nport 80\n").unwrap();
+    let outcome = Config::load_or_default_outcome(tmp.path(), Config::default());
+    let Some(FallbackReason::Malformed(e)) = outcome.fallback_reason() else {
+        panic!("expected a malformed file");
+    };
+    assert_eq!(e.line, 2);
+    assert!(outcome.fallback_reason().unwrap().is_error());
+}
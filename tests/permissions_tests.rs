@@ -0,0 +1,118 @@
+#![cfg(unix)]
+
+use config_tools::{Config, Error, ParseOptions, SecretPolicy, WarningHandler, WriteOptions};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    sync::{Arc, Mutex},
+};
+
+fn mode(path: &std::path::Path) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn test_save_with_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("credentials.ini");
+    fs::write(&path, "[Database]\npassword = old\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let config = Config::builder()
+        .section("Database")
+        .set("password", "hunter2")
+        .build();
+    let options = WriteOptions {
+        mode: Some(0o600),
+        ..WriteOptions::default()
+    };
+    config.save_with(&path, &options).unwrap();
+    assert_eq!(mode(&path), 0o600);
+
+    // Without a mode, later saves keep the restricted permissions.
+    config.save(&path).unwrap();
+    assert_eq!(mode(&path), 0o600);
+}
+
+#[test]
+fn test_secret_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("credentials.ini");
+    fs::write(&path, "[Database]\nuser = app\nPassword = hunter2\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let deny = ParseOptions {
+        secrets: SecretPolicy::Deny,
+        ..ParseOptions::default()
+    };
+    match Config::load_with(&path, &deny) {
+        Err(Error::InsecurePermissions { mode, keys, .. }) => {
+            assert_eq!(mode, 0o644);
+            assert_eq!(keys, vec!["Database:Password".to_string()]);
+        }
+        other => panic!("expected InsecurePermissions, got {other:?}"),
+    }
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let collected = Arc::clone(&warnings);
+    let warn = ParseOptions {
+        secrets: SecretPolicy::Warn,
+        on_warning: Some(WarningHandler::new(move |warning| {
+            collected.lock().unwrap().push(warning.to_string());
+        })),
+        ..ParseOptions::default()
+    };
+    assert!(Config::load_with(&path, &warn).is_ok());
+    assert!(Config::load(&path).is_ok());
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Database:Password"));
+
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    assert!(Config::load_with(&path, &deny).is_ok());
+}
+
+#[test]
+fn test_custom_secret_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(
+        &path,
+        "[Auth]\npassword_hint = ask an admin\nsigning = abc\n",
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    let options = ParseOptions {
+        secrets: SecretPolicy::Deny,
+        secret_keys: vec!["signing".to_string()],
+        ..ParseOptions::default()
+    };
+    let Err(Error::InsecurePermissions { keys, .. }) = Config::load_with(&path, &options) else {
+        panic!("expected InsecurePermissions");
+    };
+    assert_eq!(keys, vec!["Auth:signing".to_string()]);
+}
+
+#[test]
+fn test_secret_policy_applies_to_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("app.ini");
+    let credentials = dir.path().join("credentials.ini");
+    fs::write(&main, "include = credentials.ini\n[Server]\nport = 80\n").unwrap();
+    fs::write(&credentials, "[Database]\npassword = hunter2\n").unwrap();
+    fs::set_permissions(&credentials, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let options = ParseOptions {
+        includes: true,
+        secrets: SecretPolicy::Deny,
+        ..ParseOptions::default()
+    };
+    match Config::load_with(&main, &options) {
+        Err(Error::InsecurePermissions { path, .. }) => {
+            assert!(path.ends_with("credentials.ini"), "{path:?}");
+        }
+        other => panic!("expected InsecurePermissions, got {other:?}"),
+    }
+}
@@ -0,0 +1,165 @@
+use config_tools::{sectioned_defaults, Config, ParseOptions};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_with_profile_overrides() {
+    let config = sectioned_defaults! {
+        {
+            "debug" => "true",
+        }
+        ["Server"] {
+            "host" => "localhost",
+            "port" => "8080",
+        }
+        ["Server@prod"] {
+            "host" => "example.com",
+        }
+        ["Server@staging"] {
+            "host" => "staging.example.com",
+        }
+        ["@prod"] {
+            "debug" => "false",
+        }
+    };
+
+    let prod = config.with_profiles("prod", &["prod", "staging"]);
+    assert_eq!(prod.get(None, "debug"), Some("false".to_string()));
+    assert_eq!(
+        prod.get(Some("Server"), "host"),
+        Some("example.com".to_string())
+    );
+    assert_eq!(prod.get(Some("Server"), "port"), Some("8080".to_string()));
+    assert_eq!(
+        prod.sections().len(),
+        1,
+        "Profile sections should be dropped"
+    );
+
+    let dev = config.with_profile("dev");
+    assert_eq!(
+        dev.get(Some("Server"), "host"),
+        Some("localhost".to_string())
+    );
+    assert_eq!(dev.get(None, "debug"), Some("true".to_string()));
+}
+
+#[test]
+fn test_profile_section_without_base() {
+    let config = sectioned_defaults! {
+        ["Server"] {
+            "host" => "localhost",
+        }
+        ["Metrics@prod"] {
+            "endpoint" => "https://metrics.example.com",
+        }
+    };
+
+    let prod = config.with_profile("prod");
+    assert_eq!(
+        prod.get(Some("Metrics"), "endpoint"),
+        Some("https://metrics.example.com".to_string())
+    );
+    assert_eq!(prod.titles_in_order(), ["Server", "Metrics"]);
+    assert!(prod.section("Metrics@prod").is_none());
+
+    let dev = config.with_profiles("dev", &["dev", "prod"]);
+    assert!(dev.section("Metrics").is_none());
+    assert!(dev.section("Metrics@prod").is_none());
+    assert_eq!(dev.sections().len(), 1);
+}
+
+#[test]
+fn test_load_profiled() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("config.ini"),
+        "[Server]\nhost = localhost\nport = 8080\n[Server@prod]\nport = 443\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("config.prod.ini"),
+        "[Server]\nhost = example.com\n",
+    )
+    .unwrap();
+
+    let prod = Config::load_profiled(dir.path(), "prod").unwrap();
+    assert_eq!(
+        prod.get(Some("Server"), "host"),
+        Some("example.com".to_string())
+    );
+    assert_eq!(prod.get(Some("Server"), "port"), Some("443".to_string()));
+
+    // A profile without its own file still loads the base config.
+    let dev = Config::load_profiled(dir.path(), "dev").unwrap();
+    assert_eq!(
+        dev.get(Some("Server"), "host"),
+        Some("localhost".to_string())
+    );
+    assert!(Config::load_profiled(dir.path().join("missing"), "prod").is_err());
+}
+
+#[test]
+fn test_with_profile_keeps_other_titles_with_separator() {
+    let config = sectioned_defaults! {
+        ["Server"] {
+            "host" => "localhost",
+        }
+        ["Server@prod"] {
+            "host" => "example.com",
+        }
+        ["user@host"] {
+            "key" => "id_rsa",
+        }
+        ["deploy@example.com"] {
+            "key" => "id_deploy",
+        }
+    };
+
+    let prod = config.with_profiles("prod", &["prod", "staging"]);
+    assert_eq!(
+        prod.get(Some("Server"), "host"),
+        Some("example.com".to_string())
+    );
+    assert_eq!(
+        prod.get(Some("user@host"), "key"),
+        Some("id_rsa".to_string())
+    );
+    assert_eq!(
+        prod.get(Some("deploy@example.com"), "key"),
+        Some("id_deploy".to_string())
+    );
+    assert_eq!(prod.sections().len(), 3);
+}
+
+#[test]
+fn test_load_profiled_with_options() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("config.ini"),
+        "[Server]\nhost = localhost # dev box\n[Server@prod]\nport = 443\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("config.prod.ini"),
+        "[Server]\nhost = example.com # public\n",
+    )
+    .unwrap();
+
+    let options = ParseOptions {
+        inline_comments: true,
+        ..ParseOptions::default()
+    };
+    let prod = Config::load_profiled_with(dir.path(), "prod", &options).unwrap();
+    assert_eq!(
+        prod.get(Some("Server"), "host"),
+        Some("example.com".to_string())
+    );
+    assert_eq!(prod.get(Some("Server"), "port"), Some("443".to_string()));
+
+    let dev = Config::load_profiled_with(dir.path(), "dev", &options).unwrap();
+    assert_eq!(
+        dev.get(Some("Server"), "host"),
+        Some("localhost".to_string())
+    );
+}
@@ -0,0 +1,143 @@
+use config_tools::{ConfigWatcher, Error, FromSection, ParseOptions, WatchOptions};
+use std::{fs, sync::mpsc, time::Duration};
+
+#[allow(dead_code)]
+#[derive(FromSection)]
+struct ServerConfig {
+    host: String,
+    port: u16,
+}
+
+#[allow(dead_code)]
+#[derive(FromSection)]
+struct Db {
+    host: String,
+    port: u16,
+}
+
+fn options() -> WatchOptions {
+    WatchOptions {
+        poll_interval: Duration::from_millis(10),
+        debounce: Duration::from_millis(50),
+        ..WatchOptions::default()
+    }
+}
+
+#[test]
+fn test_invalid_reload_keeps_last_good_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nhost = localhost\nport = 80\n").unwrap();
+
+    let watcher = ConfigWatcher::watch(&path).unwrap();
+    let shared = watcher.shared();
+    watcher.require_section::<ServerConfig>("Server").unwrap();
+
+    fs::write(&path, "[Server]\nhost = localhost\nport = eighty\n").unwrap();
+    match watcher.reload() {
+        Err(Error::ConfigParse { section, key, .. }) => {
+            assert_eq!(section.as_deref(), Some("Server"));
+            assert_eq!(key.as_deref(), Some("port"));
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+    assert_eq!(
+        watcher.config().get(Some("Server"), "port").as_deref(),
+        Some("80")
+    );
+
+    fs::write(&path, "[Server]\nhost = localhost\nport = 8080\n").unwrap();
+    let diff = watcher.reload().unwrap();
+    assert!(diff.contains(Some("Server"), "port"));
+    assert_eq!(
+        watcher.config().get(Some("Server"), "port").as_deref(),
+        Some("8080")
+    );
+    assert_eq!(
+        shared.snapshot().get(Some("Server"), "port").as_deref(),
+        Some("8080")
+    );
+}
+
+#[test]
+fn test_watcher_reports_rejected_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nhost = localhost\nport = 80\n").unwrap();
+
+    let watcher = ConfigWatcher::watch_with(&path, options()).unwrap();
+    watcher
+        .validate(
+            |config| match config.get_as::<u16>(Some("Server"), "port") {
+                Some(port) if port >= 80 => Ok(()),
+                _ => Err(Error::ConfigParse {
+                    section: Some("Server".to_string()),
+                    key: Some("port".to_string()),
+                    message: "Port must be at least 80".to_string(),
+                }),
+            },
+        )
+        .unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    watcher.on_error(move |e| tx.send(e.to_string()).unwrap());
+    watcher.on_change(|_, _| panic!("a rejected config must not be applied"));
+
+    fs::write(&path, "[Server]\nhost = localhost\nport = 22\n").unwrap();
+    let error = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(error.contains("Port must be at least 80"), "{error}");
+    assert_eq!(
+        watcher.config().get(Some("Server"), "port").as_deref(),
+        Some("80")
+    );
+}
+
+#[test]
+fn test_validate_checks_current_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nhost = localhost\n").unwrap();
+
+    let watcher = ConfigWatcher::watch(&path).unwrap();
+    assert!(watcher.require_section::<ServerConfig>("Server").is_err());
+    assert!(watcher.require_section::<ServerConfig>("Missing").is_err());
+
+    // The failed validators weren't kept.
+    fs::write(&path, "[Server]\nhost = example.com\n").unwrap();
+    assert!(watcher.reload().is_ok());
+}
+
+#[test]
+fn test_require_section_sees_inherited_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(
+        &path,
+        "[db.primary]\nhost = primary\nport = 5432\n[db.replica : db.primary]\nhost = replica\n",
+    )
+    .unwrap();
+
+    let options = WatchOptions {
+        parse: ParseOptions {
+            inheritance: true,
+            ..ParseOptions::default()
+        },
+        ..options()
+    };
+    let watcher = ConfigWatcher::watch_with(&path, options).unwrap();
+    watcher.require_section::<Db>("db.replica").unwrap();
+
+    // The inherited port still has to be valid.
+    fs::write(
+        &path,
+        "[db.primary]\nhost = primary\nport = none\n[db.replica : db.primary]\nhost = replica\n",
+    )
+    .unwrap();
+    match watcher.reload() {
+        Err(Error::ConfigParse { section, key, .. }) => {
+            assert_eq!(section.as_deref(), Some("db.replica"));
+            assert_eq!(key.as_deref(), Some("port"));
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
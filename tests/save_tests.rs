@@ -0,0 +1,78 @@
+use config_tools::{Config, WriteOptions};
+use std::fs;
+
+#[test]
+fn test_save_replaces_file_without_leftovers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    fs::write(&path, "[Server]\nport = 80\n").unwrap();
+
+    let config = Config::builder()
+        .section("Server")
+        .set("port", "8080")
+        .build();
+    config.save(&path).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[Server]\nport=8080\n");
+    let names: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec!["config.ini"]);
+}
+
+#[test]
+fn test_save_with_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.ini");
+    let options = WriteOptions {
+        backup: true,
+        ..WriteOptions::default()
+    };
+
+    // Nothing to back up on the first save.
+    Config::builder()
+        .set("version", "1")
+        .build()
+        .save_with(&path, &options)
+        .unwrap();
+    assert!(!dir.path().join("config.ini.bak").exists());
+
+    Config::builder()
+        .set("version", "2")
+        .build()
+        .save_with(&path, &options)
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "version=2\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("config.ini.bak")).unwrap(),
+        "version=1\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_preserves_permissions_and_symlinks() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("real.ini");
+    let link = dir.path().join("config.ini");
+    fs::write(&target, "version=1\n").unwrap();
+    fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+    symlink(&target, &link).unwrap();
+
+    Config::builder()
+        .set("version", "2")
+        .build()
+        .save(&link)
+        .unwrap();
+
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "version=2\n");
+    let mode = fs::metadata(&target).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}
@@ -0,0 +1,88 @@
+use config_tools::{Config, ConfigWatcher, SharedConfig};
+use std::{fs, thread};
+
+#[test]
+fn test_snapshot_is_unaffected_by_updates() {
+    let shared = SharedConfig::new("[Server]\nport = 80\n".parse().unwrap());
+    let before = shared.snapshot();
+    assert_eq!(shared.generation(), 0);
+
+    let port = shared.update(|config| {
+        config.update(Some("Server"), "port", "8080");
+        config.get(Some("Server"), "port")
+    });
+    assert_eq!(port.as_deref(), Some("8080"));
+
+    assert_eq!(before.get(Some("Server"), "port").as_deref(), Some("80"));
+    let (after, generation) = shared.snapshot_with_generation();
+    assert_eq!(after.get(Some("Server"), "port").as_deref(), Some("8080"));
+    assert_eq!(generation, 1);
+
+    assert!(shared.replace(Config::default()).sections().is_empty());
+    assert!(shared.snapshot().sections().is_empty());
+    assert_eq!(shared.generation(), 2);
+}
+
+#[test]
+fn test_concurrent_updates() {
+    let shared = SharedConfig::default();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    let seen = shared.generation();
+                    shared.update(|config| {
+                        let count: u32 = config.get_as(None, "count").unwrap_or(0);
+                        config.update(None, "count", &(count + 1).to_string());
+                    });
+                    assert!(shared.generation() > seen);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(shared.generation(), 800);
+    assert_eq!(shared.snapshot().get_as::<u32>(None, "count"), Some(800));
+}
+
+#[test]
+fn test_watcher_shares_reloads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nport = 80\n").unwrap();
+
+    let watcher = ConfigWatcher::watch(&path).unwrap();
+    let shared = watcher.shared();
+    assert_eq!(shared.generation(), 0);
+
+    fs::write(&path, "[Server]\nport = 8080\n").unwrap();
+    watcher.reload().unwrap();
+    assert_eq!(shared.generation(), 1);
+    assert_eq!(
+        shared.snapshot().get(Some("Server"), "port").as_deref(),
+        Some("8080")
+    );
+}
+
+#[test]
+fn test_watcher_applies_changes_without_value_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "h = a\n").unwrap();
+
+    let watcher = ConfigWatcher::watch(&path).unwrap();
+    let shared = watcher.shared();
+
+    // Only the comment changes, so no value differs, but the new comment
+    // must still be picked up.
+    fs::write(&path, "; the host\nh = a\n").unwrap();
+    assert!(watcher.reload().unwrap().is_empty());
+    assert_eq!(shared.generation(), 1);
+    assert_eq!(watcher.config().comment(None, "h"), Some("the host"));
+}
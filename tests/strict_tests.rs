@@ -0,0 +1,84 @@
+use config_tools::{Config, Error, ParseOptions};
+use std::fs;
+use tempfile::NamedTempFile;
+
+fn strict() -> ParseOptions {
+    ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    }
+}
+
+fn load_strict(text: &str, options: &ParseOptions) -> Result<Config, Error> {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::write(tmp.path(), text).unwrap();
+    Config::load_with(tmp.path(), options)
+}
+
+fn strict_error(text: &str, options: &ParseOptions) -> (usize, String) {
+    match load_strict(text, options) {
+        Err(Error::Strict(e)) => (e.line, e.message),
+        other => panic!("expected a strict error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_accepts_valid_file() {
+    let text =
+        "; comment\nname = app\n\n[Server]\nhost = localhost\n\n[Child : Server]\nport = 80\n";
+    let inheritance = ParseOptions {
+        inheritance: true,
+        ..ParseOptions::default()
+    };
+    let options = ParseOptions {
+        inheritance: true,
+        ..strict()
+    };
+    let config = load_strict(text, &options).unwrap();
+
+    assert_eq!(
+        config.get(Some("Child"), "host"),
+        Some("localhost".to_string())
+    );
+    assert_eq!(load_strict(text, &inheritance).unwrap(), config);
+}
+
+#[test]
+fn test_strict_rejects_duplicates() {
+    let (line, message) = strict_error("[A]\nkey = 1\nkey = 2\n", &strict());
+    assert_eq!(line, 3);
+    assert!(message.contains("Duplicate key 'key'"));
+    assert!(message.contains("line 2"));
+
+    let (line, message) = strict_error("[A]\nkey = 1\n[B]\nkey = 1\n[A]\n", &strict());
+    assert_eq!(line, 5);
+    assert!(message.contains("Duplicate section 'A'"));
+
+    // Without strict mode the last value wins.
+    let config = load_strict("[A]\nkey = 1\nkey = 2\n", &ParseOptions::default()).unwrap();
+    assert_eq!(config.get(Some("A"), "key"), Some("2".to_string()));
+}
+
+#[test]
+fn test_strict_rejects_malformed_lines() {
+    assert_eq!(strict_error("[A]\n= value\n", &strict()).0, 2);
+    assert_eq!(strict_error("[A]\nke]y = value\n", &strict()).0, 2);
+    assert_eq!(strict_error("[]\n", &strict()).0, 1);
+
+    let (line, message) = strict_error("[A]\nkey = 1\njust text\n", &strict());
+    assert_eq!(line, 3);
+    assert!(message.contains("after 'just text'"));
+}
+
+#[test]
+fn test_strict_general_keys() {
+    let options = ParseOptions {
+        allow_general: false,
+        ..strict()
+    };
+    let (line, message) = strict_error("; header\nname = app\n[A]\n", &options);
+    assert_eq!(line, 2);
+    assert!(message.contains("outside of any section"));
+
+    assert!(load_strict("[A]\nname = app\n", &options).is_ok());
+}
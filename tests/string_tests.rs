@@ -0,0 +1,44 @@
+use config_tools::Config;
+use std::io::Cursor;
+
+const FIXTURE: &str = "\
+debug = true
+
+[Server]
+host = localhost
+port = 8080
+";
+
+#[test]
+fn test_from_str() {
+    let config: Config = FIXTURE.parse().unwrap();
+
+    assert_eq!(config.get(None, "debug"), Some("true".to_string()));
+    assert_eq!(config.get_as::<u16>(Some("Server"), "port"), Some(8080));
+    assert!("[Server\nport = 1".parse::<Config>().is_err());
+}
+
+#[test]
+fn test_from_reader() {
+    let config = Config::from_reader(Cursor::new(FIXTURE)).unwrap();
+    assert_eq!(config, FIXTURE.parse().unwrap());
+}
+
+#[test]
+fn test_display_and_write_to() {
+    let config = Config::builder()
+        .set("debug", "true")
+        .section("Server")
+        .set("host", "localhost")
+        .build();
+
+    let expected = "debug=true\n\n[Server]\nhost=localhost\n";
+    assert_eq!(config.to_string(), expected);
+
+    let mut buffer = Vec::new();
+    config.write_to(&mut buffer).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+    let reparsed: Config = config.to_string().parse().unwrap();
+    assert_eq!(reparsed, config);
+}
@@ -0,0 +1,89 @@
+use config_tools::{ConfigWatcher, WatchOptions};
+use std::{fs, sync::mpsc, time::Duration};
+
+fn options() -> WatchOptions {
+    WatchOptions {
+        poll_interval: Duration::from_millis(10),
+        debounce: Duration::from_millis(50),
+        ..WatchOptions::default()
+    }
+}
+
+#[test]
+fn test_watcher_reloads_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nport = 80\n").unwrap();
+
+    let watcher = ConfigWatcher::watch_with(&path, options()).unwrap();
+    let (tx, rx) = mpsc::channel();
+    watcher.on_change(move |config, diff| {
+        tx.send((config.get(Some("Server"), "port"), diff.clone()))
+            .unwrap();
+    });
+
+    for port in 8080..8085 {
+        fs::write(&path, format!("[Server]\nport = {port}\n")).unwrap();
+    }
+
+    // Quick writes are usually picked up as a single reload, but a slow
+    // machine may see some of them, so wait for the last one.
+    let (port, diff) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].old.as_deref(), Some("80"));
+
+    let mut port = port;
+    while port.as_deref() != Some("8084") {
+        port = rx.recv_timeout(Duration::from_secs(5)).unwrap().0;
+    }
+    assert_eq!(
+        watcher.config().get(Some("Server"), "port").as_deref(),
+        Some("8084")
+    );
+}
+
+#[test]
+fn test_watcher_keeps_config_on_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Server]\nport = 80\n").unwrap();
+
+    let watcher = ConfigWatcher::watch_with(&path, options()).unwrap();
+    let (tx, rx) = mpsc::channel();
+    watcher.on_error(move |e| tx.send(e.to_string()).unwrap());
+
+    fs::write(&path, "[Server\nport = 8080\n").unwrap();
+    let error = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(error.contains("app.ini"), "{error}");
+    assert_eq!(
+        watcher.config().get(Some("Server"), "port").as_deref(),
+        Some("80")
+    );
+}
+
+#[test]
+fn test_watcher_reports_repeated_key_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[Upstream]\nserver = a\nserver = b\n").unwrap();
+
+    let watcher = ConfigWatcher::watch(&path).unwrap();
+    let (tx, rx) = mpsc::channel();
+    watcher.on_change(move |_, diff| tx.send(diff.clone()).unwrap());
+
+    // Only the first value changes; the last one, which get returns, stays.
+    fs::write(&path, "[Upstream]\nserver = c\nserver = b\n").unwrap();
+    let diff = watcher.reload().unwrap();
+    assert!(diff.contains(Some("Upstream"), "server"));
+
+    let notified = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(
+        notified.get(Some("Upstream"), "server").unwrap().new_values,
+        ["c", "b"]
+    );
+}
+
+#[test]
+fn test_watch_missing_file() {
+    assert!(ConfigWatcher::watch("nonexistent_file.ini").is_err());
+}